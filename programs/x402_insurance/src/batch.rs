@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer};
+
+use crate::errors::InsuranceError;
+use crate::state::InsuranceClaim;
+use crate::tombstone::{self, CLAIM_TOMBSTONE_SEED};
+
+/// Check a batch item's claim and tombstone accounts and return the claim bump
+///
+/// The claim must be the scoped PDA of the commitment and not yet exist, and
+/// the commitment must never have been closed before.
+pub fn check_new_claim(
+    claim: &AccountInfo,
+    claim_tombstone: &AccountInfo,
+    client: &Pubkey,
+    provider: &Pubkey,
+    request_commitment: &[u8; 32],
+) -> Result<u8> {
+    let (claim_key, bump) = Pubkey::find_program_address(
+        &[b"claim", client.as_ref(), provider.as_ref(), request_commitment],
+        &crate::ID,
+    );
+    require_keys_eq!(claim.key(), claim_key, InsuranceError::InvalidClaimAddress);
+    require!(
+        claim.owner == &system_program::ID && claim.data_is_empty(),
+        InsuranceError::ClaimAlreadyExists
+    );
+
+    let (tombstone_key, _) = Pubkey::find_program_address(
+        &[CLAIM_TOMBSTONE_SEED, claim_key.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(claim_tombstone.key(), tombstone_key, InsuranceError::InvalidClaimAddress);
+    require!(
+        !tombstone::is_tombstoned(claim_tombstone),
+        InsuranceError::CommitmentAlreadyUsed
+    );
+
+    Ok(bump)
+}
+
+/// Create a claim PDA funded by `payer` and write `claim` into it
+///
+/// Mirrors Anchor's `init`, including topping up an address that was
+/// pre-funded with stray lamports.
+pub fn create_claim_account<'info>(
+    claim_info: &AccountInfo<'info>,
+    claim: &InsuranceClaim,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let bump = [claim.bump];
+    let seeds = &[
+        b"claim".as_ref(),
+        claim.client.as_ref(),
+        claim.provider.as_ref(),
+        claim.request_commitment.as_ref(),
+        &bump,
+    ];
+    let signer = &[&seeds[..]];
+
+    let space = InsuranceClaim::LEN;
+    let rent = Rent::get()?.minimum_balance(space);
+    if claim_info.lamports() == 0 {
+        let cpi_accounts = CreateAccount {
+            from: payer.clone(),
+            to: claim_info.clone(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer);
+        system_program::create_account(cpi_ctx, rent, space as u64, &crate::ID)?;
+    } else {
+        let top_up = rent.saturating_sub(claim_info.lamports());
+        if top_up > 0 {
+            let cpi_accounts = Transfer {
+                from: payer.clone(),
+                to: claim_info.clone(),
+            };
+            let cpi_ctx = CpiContext::new(system_program.clone(), cpi_accounts);
+            system_program::transfer(cpi_ctx, top_up)?;
+        }
+        let cpi_accounts = Allocate {
+            account_to_allocate: claim_info.clone(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer);
+        system_program::allocate(cpi_ctx, space as u64)?;
+
+        let cpi_accounts = Assign {
+            account_to_assign: claim_info.clone(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer);
+        system_program::assign(cpi_ctx, &crate::ID)?;
+    }
+
+    let mut data = claim_info.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    claim.try_serialize(&mut writer)
+}
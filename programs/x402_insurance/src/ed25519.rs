@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+use solana_sdk_ids::ed25519_program;
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+
+use crate::errors::InsuranceError;
+
+/// Domain separator for provider delivery proofs
+pub const DELIVERY_PROOF_DOMAIN: &[u8] = b"x402-insurance:delivery:v1";
+/// Domain separator for client delivery receipts
+pub const DELIVERY_RECEIPT_DOMAIN: &[u8] = b"x402-insurance:receipt:v1";
+/// Domain separator for client session receipts
+pub const SESSION_RECEIPT_DOMAIN: &[u8] = b"x402-insurance:session-receipt:v1";
+/// Domain separator for provider payment quotes
+pub const PAYMENT_QUOTE_DOMAIN: &[u8] = b"x402-insurance:quote:v1";
+
+/// Size of the Ed25519 instruction header (num_signatures + padding)
+const SIGNATURE_OFFSETS_START: usize = 2;
+/// Size of one serialized `Ed25519SignatureOffsets` entry
+const SIGNATURE_OFFSETS_SERIALIZED_SIZE: usize = 14;
+const PUBKEY_SERIALIZED_SIZE: usize = 32;
+const SIGNATURE_SERIALIZED_SIZE: usize = 64;
+
+/// A signature the Ed25519 program has already verified in this transaction
+pub struct VerifiedSignature {
+    pub signer: Pubkey,
+    pub signature: [u8; 64],
+    pub message: Vec<u8>,
+}
+
+/// Canonical message a provider signs to prove delivery for a claim
+pub fn delivery_proof_message(
+    claim: &Pubkey,
+    request_commitment: &[u8; 32],
+    payment_amount: u64,
+    deadline: i64,
+) -> Vec<u8> {
+    claim_message(DELIVERY_PROOF_DOMAIN, claim, request_commitment, payment_amount, deadline)
+}
+
+/// Canonical message a client signs to acknowledge receipt for a claim
+pub fn delivery_receipt_message(
+    claim: &Pubkey,
+    request_commitment: &[u8; 32],
+    payment_amount: u64,
+    deadline: i64,
+) -> Vec<u8> {
+    claim_message(DELIVERY_RECEIPT_DOMAIN, claim, request_commitment, payment_amount, deadline)
+}
+
+/// Canonical message a client signs to acknowledge a session's first
+/// `served_count` requests as served
+///
+/// The request hash is left out so that recording further requests does not
+/// invalidate a receipt the provider has yet to submit.
+pub fn session_receipt_message(session: &Pubkey, served_count: u64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(SESSION_RECEIPT_DOMAIN.len() + 32 + 32 + 8);
+    message.extend_from_slice(SESSION_RECEIPT_DOMAIN);
+    message.extend_from_slice(crate::ID.as_ref());
+    message.extend_from_slice(session.as_ref());
+    message.extend_from_slice(&served_count.to_le_bytes());
+    message
+}
+
+/// Canonical message a provider signs to quote a request before purchase
+pub fn payment_quote_message(
+    request_commitment: &[u8; 32],
+    mint: &Pubkey,
+    price: u64,
+    max_timeout_seconds: u64,
+    expiry_slot: u64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(PAYMENT_QUOTE_DOMAIN.len() + 32 + 32 + 32 + 8 + 8 + 8);
+    message.extend_from_slice(PAYMENT_QUOTE_DOMAIN);
+    message.extend_from_slice(crate::ID.as_ref());
+    message.extend_from_slice(request_commitment);
+    message.extend_from_slice(mint.as_ref());
+    message.extend_from_slice(&price.to_le_bytes());
+    message.extend_from_slice(&max_timeout_seconds.to_le_bytes());
+    message.extend_from_slice(&expiry_slot.to_le_bytes());
+    message
+}
+
+fn claim_message(
+    domain: &[u8],
+    claim: &Pubkey,
+    request_commitment: &[u8; 32],
+    payment_amount: u64,
+    deadline: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(domain.len() + 32 + 32 + 32 + 8 + 8);
+    message.extend_from_slice(domain);
+    message.extend_from_slice(crate::ID.as_ref());
+    message.extend_from_slice(claim.as_ref());
+    message.extend_from_slice(request_commitment);
+    message.extend_from_slice(&payment_amount.to_le_bytes());
+    message.extend_from_slice(&deadline.to_le_bytes());
+    message
+}
+
+/// Load the signatures verified by the Ed25519 program instruction
+/// immediately preceding the currently executing instruction.
+///
+/// Only offsets pointing into the Ed25519 instruction itself are accepted,
+/// so the pubkey, signature and message we read are exactly what was verified.
+pub fn load_preceding_signatures(instructions_sysvar: &AccountInfo) -> Result<Vec<VerifiedSignature>> {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, InsuranceError::MissingEd25519Instruction);
+
+    let ix = load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+    require!(
+        ix.program_id == ed25519_program::ID,
+        InsuranceError::MissingEd25519Instruction
+    );
+    require!(ix.accounts.is_empty(), InsuranceError::MalformedEd25519Instruction);
+
+    let data = &ix.data;
+    require!(
+        data.len() >= SIGNATURE_OFFSETS_START,
+        InsuranceError::MalformedEd25519Instruction
+    );
+    let num_signatures = data[0] as usize;
+    require!(num_signatures > 0, InsuranceError::MalformedEd25519Instruction);
+    require!(
+        data.len() >= SIGNATURE_OFFSETS_START + num_signatures * SIGNATURE_OFFSETS_SERIALIZED_SIZE,
+        InsuranceError::MalformedEd25519Instruction
+    );
+
+    let mut signatures = Vec::with_capacity(num_signatures);
+    for i in 0..num_signatures {
+        let start = SIGNATURE_OFFSETS_START + i * SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+        let offsets = &data[start..start + SIGNATURE_OFFSETS_SERIALIZED_SIZE];
+        let read_u16 = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+
+        let signature_offset = read_u16(0) as usize;
+        let signature_instruction_index = read_u16(2);
+        let public_key_offset = read_u16(4) as usize;
+        let public_key_instruction_index = read_u16(6);
+        let message_data_offset = read_u16(8) as usize;
+        let message_data_size = read_u16(10) as usize;
+        let message_instruction_index = read_u16(12);
+
+        require!(
+            signature_instruction_index == u16::MAX
+                && public_key_instruction_index == u16::MAX
+                && message_instruction_index == u16::MAX,
+            InsuranceError::MalformedEd25519Instruction
+        );
+
+        let signature = data
+            .get(signature_offset..signature_offset + SIGNATURE_SERIALIZED_SIZE)
+            .ok_or(InsuranceError::MalformedEd25519Instruction)?;
+        let signer = data
+            .get(public_key_offset..public_key_offset + PUBKEY_SERIALIZED_SIZE)
+            .ok_or(InsuranceError::MalformedEd25519Instruction)?;
+        let message = data
+            .get(message_data_offset..message_data_offset + message_data_size)
+            .ok_or(InsuranceError::MalformedEd25519Instruction)?;
+
+        signatures.push(VerifiedSignature {
+            signer: Pubkey::try_from(signer).map_err(|_| InsuranceError::MalformedEd25519Instruction)?,
+            signature: signature
+                .try_into()
+                .map_err(|_| InsuranceError::MalformedEd25519Instruction)?,
+            message: message.to_vec(),
+        });
+    }
+
+    Ok(signatures)
+}
+
+/// Require that the preceding Ed25519 instruction verified exactly one
+/// signature by `signer` over `message`, optionally pinning the signature bytes.
+pub fn verify_preceding_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+    signature: Option<&[u8; 64]>,
+) -> Result<()> {
+    let signatures = load_preceding_signatures(instructions_sysvar)?;
+    require!(signatures.len() == 1, InsuranceError::MalformedEd25519Instruction);
+
+    let verified = &signatures[0];
+    require!(
+        verified.signer == *signer
+            && signature.is_none_or(|signature| verified.signature == *signature)
+            && verified.message == message,
+        InsuranceError::InvalidSignature
+    );
+
+    Ok(())
+}
@@ -40,4 +40,187 @@ pub enum InsuranceError {
 
     #[msg("Grace period has not expired yet")]
     GracePeriodNotExpired,
+
+    #[msg("Ed25519 verification instruction not found")]
+    MissingEd25519Instruction,
+
+    #[msg("Malformed Ed25519 verification instruction")]
+    MalformedEd25519Instruction,
+
+    #[msg("Signer is not the protocol authority")]
+    Unauthorized,
+
+    #[msg("Penalty rate exceeds 10000 basis points")]
+    InvalidPenaltyRate,
+
+    #[msg("Timeout is outside the allowed range")]
+    InvalidTimeout,
+
+    #[msg("Liquidation grace period is outside the allowed range")]
+    InvalidGracePeriod,
+
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+
+    #[msg("Coverage multiplier does not cover a full refund plus penalty")]
+    InvalidCoverageMultiplier,
+
+    #[msg("Token account mint does not match the bond mint")]
+    MintMismatch,
+
+    #[msg("Token account is not owned by the expected wallet")]
+    InvalidTokenAccountOwner,
+
+    #[msg("Token account is not owned by the platform treasury")]
+    InvalidTreasuryAccount,
+
+    #[msg("Mint has a Token-2022 extension the vault cannot support")]
+    UnsupportedMintExtension,
+
+    #[msg("Claim is not settled and cannot be closed")]
+    ClaimNotSettled,
+
+    #[msg("Request commitment was already used by a closed claim")]
+    CommitmentAlreadyUsed,
+
+    #[msg("Rent recipient is not the claim payer")]
+    InvalidPayer,
+
+    #[msg("Closing the claim requires the payer, tombstone and system program accounts")]
+    MissingCloseAccounts,
+
+    #[msg("Request commitment is not derived from the provider's challenge for this client")]
+    InvalidRequestCommitment,
+
+    #[msg("Claim account does not match the request commitment")]
+    InvalidClaimAddress,
+
+    #[msg("Provider requires a signed quote for this purchase")]
+    QuoteRequired,
+
+    #[msg("Purchase terms do not match the provider's quote")]
+    QuoteMismatch,
+
+    #[msg("Provider quote has expired")]
+    QuoteExpired,
+
+    #[msg("Provider policy is malformed")]
+    InvalidPolicy,
+
+    #[msg("Provider is not accepting new insurance")]
+    ProviderPaused,
+
+    #[msg("Provider does not insure this mint")]
+    MintNotAllowed,
+
+    #[msg("Payment exceeds the provider's maximum per claim")]
+    PaymentExceedsPolicy,
+
+    #[msg("Timeout is outside the provider's accepted range")]
+    TimeoutOutsidePolicy,
+
+    #[msg("Provider has reached its maximum concurrent claims")]
+    TooManyActiveClaims,
+
+    #[msg("Purchase would exceed the provider's maximum locked exposure")]
+    ExposureLimitExceeded,
+
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+
+    #[msg("Dispute window exceeds the allowed maximum")]
+    InvalidDisputeWindow,
+
+    #[msg("Disputes are not enabled for this claim")]
+    DisputesDisabled,
+
+    #[msg("Dispute window has closed")]
+    DisputeWindowClosed,
+
+    #[msg("Dispute window is still open")]
+    DisputeWindowOpen,
+
+    #[msg("Claim is not disputed")]
+    NotDisputed,
+
+    #[msg("Signer is not the claim's arbiter")]
+    NotArbiter,
+
+    #[msg("Delivered fraction exceeds 10000 basis points")]
+    InvalidDeliveredFraction,
+
+    #[msg("Batch items do not match the accounts supplied")]
+    InvalidBatch,
+
+    #[msg("Claim account already exists")]
+    ClaimAlreadyExists,
+
+    #[msg("Session parameters are invalid")]
+    InvalidSession,
+
+    #[msg("Session has expired")]
+    SessionExpired,
+
+    #[msg("Session budget is exhausted")]
+    SessionBudgetExhausted,
+
+    #[msg("Served count must increase and cannot exceed the session budget")]
+    InvalidServedCount,
+
+    #[msg("Only the provider can close a session before it expires")]
+    SessionNotExpired,
+
+    #[msg("Escrowed claim requires the vault and provider token accounts")]
+    MissingEscrowAccounts,
+
+    #[msg("Crank delay exceeds the maximum allowed")]
+    InvalidCrankDelay,
+
+    #[msg("Crank tip exceeds 10000 basis points")]
+    InvalidCrankTip,
+
+    #[msg("Provider is not liquidated")]
+    ProviderNotLiquidated,
+
+    #[msg("Liquidated provider still has pending claims")]
+    WindDownIncomplete,
+
+    #[msg("Liquidation bonus must start at or below its maximum of at most 10000 basis points")]
+    InvalidLiquidationBonus,
+
+    #[msg("Reinstatement bond must be at least min_bond and the cool-down within bounds")]
+    InvalidReinstatementTerms,
+
+    #[msg("Liquidated provider's residual bond has not been swept")]
+    ResidualNotSwept,
+
+    #[msg("Reinstatement cool-down has not elapsed")]
+    ReinstatementCooldown,
+
+    #[msg("Reinstatement requires the authority's approval")]
+    ReinstatementNotApproved,
+
+    #[msg("Deposit does not cover the reinstatement bond")]
+    InsufficientReinstatementBond,
+
+    #[msg("Liquidation penalty exceeds 10000 basis points")]
+    InvalidLiquidationPenalty,
+
+    #[msg("Transfer would draw on payments escrowed in the vault")]
+    EscrowShortfall,
+
+    #[msg("Signer is not the session's client or provider")]
+    NotSessionParty,
+
+    #[msg("Account is not a legacy account of the expected type")]
+    InvalidLegacyAccount,
+
+    #[msg("Legacy bond still has claims locked against it; migrate them first")]
+    LegacyClaimsPending,
+
+    #[msg("Arbiter's deadline to rule on this dispute has passed")]
+    RulingDeadlinePassed,
+
+    #[msg("Arbiter may still rule on this dispute")]
+    RulingDeadlineNotReached,
 }
@@ -0,0 +1,526 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{
+    CollateralTransition, DisputeRuling, InsuranceConfig, PayoutTier, ProviderBond, SlashReason,
+};
+
+/// Protocol parameters were set (on `initialize` and every `update_config`)
+#[event]
+pub struct ConfigUpdated {
+    pub authority: Pubkey,
+    pub platform_treasury: Pubkey,
+    pub platform_penalty_rate: u16,
+    pub coverage_multiplier_bps: u16,
+    pub default_timeout: u64,
+    pub liquidation_grace_period: u64,
+    pub default_min_bond: u64,
+    pub dispute_window: u64,
+    pub arbiter: Pubkey,
+    pub crank_delay: u64,
+    pub crank_tip_bps: u16,
+    pub liquidation_bonus_bps: u16,
+    pub max_liquidation_bonus_bps: u16,
+    pub liquidation_bonus_ramp: u64,
+    pub reinstatement_bond_bps: u16,
+    pub reinstatement_cooldown: u64,
+    pub reinstatement_requires_approval: bool,
+    pub liquidation_penalty_bps: u16,
+    pub dispute_loss_penalty_bps: u16,
+    pub invalid_signature_penalty_bps: u16,
+    pub timestamp: i64,
+}
+
+impl ConfigUpdated {
+    pub fn from_config(config: &InsuranceConfig, timestamp: i64) -> Self {
+        Self {
+            authority: config.authority,
+            platform_treasury: config.platform_treasury,
+            platform_penalty_rate: config.platform_penalty_rate,
+            coverage_multiplier_bps: config.coverage_multiplier_bps,
+            default_timeout: config.default_timeout,
+            liquidation_grace_period: config.liquidation_grace_period,
+            default_min_bond: config.default_min_bond,
+            dispute_window: config.dispute_window,
+            arbiter: config.arbiter,
+            crank_delay: config.crank_delay,
+            crank_tip_bps: config.crank_tip_bps,
+            liquidation_bonus_bps: config.liquidation_bonus_bps,
+            max_liquidation_bonus_bps: config.max_liquidation_bonus_bps,
+            liquidation_bonus_ramp: config.liquidation_bonus_ramp,
+            reinstatement_bond_bps: config.reinstatement_bond_bps,
+            reinstatement_cooldown: config.reinstatement_cooldown,
+            reinstatement_requires_approval: config.reinstatement_requires_approval,
+            liquidation_penalty_bps: config.liquidation_penalty_bps,
+            dispute_loss_penalty_bps: config.dispute_loss_penalty_bps,
+            invalid_signature_penalty_bps: config.invalid_signature_penalty_bps,
+            timestamp,
+        }
+    }
+}
+
+/// A new authority was proposed (default pubkey cancels the proposal)
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// The pending authority accepted the handover
+#[event]
+pub struct AuthorityTransferred {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Authority paused or unpaused the protocol
+#[event]
+pub struct ProtocolPauseUpdated {
+    pub authority: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+/// Provider paused or resumed accepting new insurance
+#[event]
+pub struct ProviderPauseUpdated {
+    pub provider: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+/// Provider deposited bond; `received` is what the vault was credited
+#[event]
+pub struct BondDeposited {
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub received: u64,
+    pub total_bond: u64,
+    pub locked_bond: u64,
+    pub timestamp: i64,
+}
+
+/// Authority changed a provider's minimum bond
+#[event]
+pub struct MinBondUpdated {
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub previous_min_bond: u64,
+    pub min_bond: u64,
+    pub timestamp: i64,
+}
+
+/// Provider changed its coverage multiplier override
+#[event]
+pub struct CoverageMultiplierUpdated {
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    /// Override in basis points (0 falls back to the config default)
+    pub coverage_multiplier_bps: u16,
+    pub timestamp: i64,
+}
+
+/// Provider changed whether purchases must carry a signed quote
+#[event]
+pub struct QuoteRequirementUpdated {
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub requires_quote: bool,
+    pub timestamp: i64,
+}
+
+/// Provider replaced its coverage policy
+#[event]
+pub struct ProviderPolicyUpdated {
+    pub provider: Pubkey,
+    pub max_payment_amount: u64,
+    pub min_timeout_seconds: u64,
+    pub max_timeout_seconds: u64,
+    pub max_concurrent_claims: u32,
+    pub max_locked_exposure: u64,
+    pub allowed_mints: Vec<Pubkey>,
+    pub paused: bool,
+    pub arbiter: Pubkey,
+    pub payout_schedule: Vec<PayoutTier>,
+    pub escrow_payments: bool,
+    pub timestamp: i64,
+}
+
+/// Client paid the provider and bond was locked for the request
+#[event]
+pub struct InsurancePurchased {
+    pub claim: Pubkey,
+    pub request_commitment: [u8; 32],
+    /// Provider's 402 challenge the commitment is derived from
+    pub challenge: [u8; 32],
+    pub client: Pubkey,
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub payment_amount: u64,
+    pub locked_amount: u64,
+    pub refund_amount: u64,
+    pub penalty_amount: u64,
+    pub deadline: i64,
+    /// Whether the purchase carried a provider-signed quote
+    pub quoted: bool,
+    /// Arbiter snapshotted for disputes (default if disabled)
+    pub arbiter: Pubkey,
+    /// Payment held in escrow until confirmation (0 when paid up front)
+    pub escrow_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Claim confirmed by the provider's proof or the client's receipt
+#[event]
+pub struct ServiceConfirmed {
+    pub claim: Pubkey,
+    pub request_commitment: [u8; 32],
+    pub client: Pubkey,
+    pub provider: Pubkey,
+    /// Provider or client whose signature confirmed delivery
+    pub confirmed_by: Pubkey,
+    /// Share of the service the provider reports as delivered (in basis points)
+    pub delivered_fraction_bps: u16,
+    /// Share of the timeout payout owed for partial or late delivery
+    pub payout_bps: u16,
+    pub refund_amount: u64,
+    /// Refund actually received (net of any Token-2022 transfer fee)
+    pub client_received: u64,
+    pub penalty_amount: u64,
+    /// Escrowed payment returned to the client for partial or late delivery
+    pub escrow_refunded: u64,
+    /// Escrowed payment released to the provider (0 while the dispute window is open)
+    pub escrow_released: u64,
+    /// Bond unlocked now (0 while the dispute window keeps it locked)
+    pub unlocked_amount: u64,
+    /// End of the dispute window (0 if none)
+    pub dispute_deadline: i64,
+    pub timestamp: i64,
+}
+
+/// Client disputed a confirmed claim within the window
+#[event]
+pub struct ClaimDisputed {
+    pub claim: Pubkey,
+    pub request_commitment: [u8; 32],
+    pub client: Pubkey,
+    pub provider: Pubkey,
+    pub arbiter: Pubkey,
+    /// Default ruling applies if the arbiter has not ruled by then
+    pub ruling_deadline: i64,
+    pub timestamp: i64,
+}
+
+/// Arbiter ruled on a disputed claim, or its ruling deadline passed
+#[event]
+pub struct DisputeResolved {
+    pub claim: Pubkey,
+    pub request_commitment: [u8; 32],
+    pub arbiter: Pubkey,
+    pub ruling: DisputeRuling,
+    /// Whether the arbiter missed its deadline and the default ruling applied
+    pub defaulted: bool,
+    pub refund_amount: u64,
+    pub client_received: u64,
+    pub penalty_amount: u64,
+    /// Extra penalty taken from available bond at the ruling's slash rate
+    pub slash_penalty: u64,
+    pub unlocked_amount: u64,
+    /// Escrowed payment returned to the client
+    pub escrow_refunded: u64,
+    /// Escrowed payment released to the provider
+    pub escrow_released: u64,
+    pub timestamp: i64,
+}
+
+/// Dispute window closed undisputed and the confirmed claim's bond was unlocked
+#[event]
+pub struct BondReleased {
+    pub claim: Pubkey,
+    pub request_commitment: [u8; 32],
+    pub provider: Pubkey,
+    pub unlocked_amount: u64,
+    /// Escrowed payment released to the provider
+    pub escrow_released: u64,
+    pub timestamp: i64,
+}
+
+/// Client was refunded from the provider's bond after timeout
+#[event]
+pub struct InsuranceClaimed {
+    pub claim: Pubkey,
+    pub request_commitment: [u8; 32],
+    pub client: Pubkey,
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub refund_amount: u64,
+    /// Refund that reached the client net of Token-2022 transfer fees
+    pub client_received: u64,
+    pub penalty_amount: u64,
+    pub total_bond: u64,
+    /// Escrowed payment returned to the client
+    pub escrow_refunded: u64,
+    pub timestamp: i64,
+}
+
+/// A third party settled an expired claim on the client's behalf
+///
+/// Emitted after the claim's `InsuranceClaimed`.
+#[event]
+pub struct ExpiredClaimSettled {
+    pub claim: Pubkey,
+    pub request_commitment: [u8; 32],
+    pub cranker: Pubkey,
+    /// Share of the penalty paid to the cranker instead of the treasury
+    pub tip_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Settled claim closed; its rent (less the tombstone's) went back to the payer
+#[event]
+pub struct ClaimClosed {
+    pub claim: Pubkey,
+    pub request_commitment: [u8; 32],
+    pub payer: Pubkey,
+    pub rent_refunded: u64,
+    pub timestamp: i64,
+}
+
+/// Client prepaid a session and the provider's bond was locked for its budget
+#[event]
+pub struct SessionOpened {
+    pub session: Pubkey,
+    pub client: Pubkey,
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub price_per_request: u64,
+    pub max_requests: u64,
+    pub locked_amount: u64,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+/// Client recorded requests under a session
+#[event]
+pub struct SessionRequestsRecorded {
+    pub session: Pubkey,
+    /// Requests recorded by this instruction
+    pub recorded: u64,
+    pub request_count: u64,
+    pub request_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// Provider confirmed serving a session's requests
+#[event]
+pub struct SessionServed {
+    pub session: Pubkey,
+    pub served_count: u64,
+    pub request_count: u64,
+    /// Request log the client's receipt was confirmed against
+    pub request_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// Session settled: unserved requests refunded and the bond unlocked
+#[event]
+pub struct SessionClosed {
+    pub session: Pubkey,
+    pub client: Pubkey,
+    pub provider: Pubkey,
+    pub served_count: u64,
+    pub request_count: u64,
+    pub request_hash: [u8; 32],
+    pub refund_amount: u64,
+    /// Refund that reached the client net of Token-2022 transfer fees
+    pub client_received: u64,
+    pub penalty_amount: u64,
+    pub unlocked_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Provider withdrew available bond
+#[event]
+pub struct BondWithdrawn {
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub total_bond: u64,
+    pub timestamp: i64,
+}
+
+/// Available bond fell below `min_bond`; the grace period started
+#[event]
+pub struct ProviderUndercollateralized {
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub available_bond: u64,
+    pub min_bond: u64,
+    pub timestamp: i64,
+}
+
+/// Available bond is back at or above `min_bond`
+#[event]
+pub struct ProviderRestored {
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub available_bond: u64,
+    pub min_bond: u64,
+    pub timestamp: i64,
+}
+
+/// Provider was liquidated and the seized bond split between the liquidator
+/// and the treasury
+#[event]
+pub struct ProviderLiquidated {
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub liquidator: Pubkey,
+    /// Only the liquidation penalty was seized and the provider keeps operating
+    pub partial: bool,
+    pub seized_amount: u64,
+    /// Bonus rate in effect at liquidation
+    pub bonus_bps: u16,
+    /// Part of `seized_amount` paid to the liquidator
+    pub liquidator_bonus: u64,
+    pub remaining_locked: u64,
+    /// Claims left to settle before the bond can be swept
+    pub pending_claims: u32,
+    pub timestamp: i64,
+}
+
+/// A liquidated provider's remaining bond changed during wind-down
+#[event]
+pub struct LiquidationWindDown {
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub remaining_locked: u64,
+    pub pending_claims: u32,
+    /// Unlocked bond awaiting `sweep_liquidated_bond`
+    pub residual: u64,
+    pub timestamp: i64,
+}
+
+/// Residual bond of a fully wound-down provider sent to the treasury
+#[event]
+pub struct LiquidatedBondSwept {
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Liquidated provider brought back a fresh bond and can be insured again
+#[event]
+pub struct ProviderReinstated {
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    /// Amount credited after Token-2022 transfer fees
+    pub received: u64,
+    pub min_bond: u64,
+    /// Authority that approved, or default if approval was not required
+    pub approved_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Provider's bond was slashed and the penalty added to its ledger
+#[event]
+pub struct ProviderSlashed {
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub reason: SlashReason,
+    pub amount: u64,
+    /// Lifetime total after this slash
+    pub total_slashed: u64,
+    /// Number of slashes for this reason, including this one
+    pub reason_count: u32,
+    pub timestamp: i64,
+}
+
+/// Claim created by a pre-mint release was settled (if still pending) and closed
+#[event]
+pub struct LegacyClaimMigrated {
+    pub claim: Pubkey,
+    pub request_commitment: [u8; 32],
+    pub client: Pubkey,
+    pub provider: Pubkey,
+    /// Refund paid to the client if the claim timed out (0 otherwise)
+    pub refund_amount: u64,
+    /// Penalty paid to the treasury if the claim timed out (0 otherwise)
+    pub penalty_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Bond held under the pre-mint `["provider_bond", provider]` PDA moved to
+/// its mint-keyed bond, or to the treasury if the provider was liquidated
+#[event]
+pub struct LegacyBondMigrated {
+    pub provider: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    /// Amount credited to the new bond (0 if swept to the treasury)
+    pub received: u64,
+    pub swept: bool,
+    pub total_bond: u64,
+    pub timestamp: i64,
+}
+
+/// Record a slash in the provider's ledger and emit `ProviderSlashed`
+///
+/// Does nothing for a zero amount.
+pub fn record_slash(
+    provider_bond: &mut ProviderBond,
+    reason: SlashReason,
+    amount: u64,
+    timestamp: i64,
+) -> Result<()> {
+    if provider_bond.record_slash(reason, amount, timestamp)? {
+        emit!(ProviderSlashed {
+            provider: provider_bond.provider,
+            mint: provider_bond.mint,
+            reason,
+            amount,
+            total_slashed: provider_bond.total_slashed,
+            reason_count: provider_bond.slash_counts[reason as usize],
+            timestamp,
+        });
+    }
+    Ok(())
+}
+
+/// Emit the event matching a change in a provider's collateral health
+pub fn emit_collateral_transition(
+    provider_bond: &ProviderBond,
+    transition: CollateralTransition,
+    timestamp: i64,
+) -> Result<()> {
+    match transition {
+        CollateralTransition::Undercollateralized => emit!(ProviderUndercollateralized {
+            provider: provider_bond.provider,
+            mint: provider_bond.mint,
+            available_bond: provider_bond.available_bond()?,
+            min_bond: provider_bond.min_bond,
+            timestamp,
+        }),
+        CollateralTransition::Restored => emit!(ProviderRestored {
+            provider: provider_bond.provider,
+            mint: provider_bond.mint,
+            available_bond: provider_bond.available_bond()?,
+            min_bond: provider_bond.min_bond,
+            timestamp,
+        }),
+        CollateralTransition::WindingDown => emit!(LiquidationWindDown {
+            provider: provider_bond.provider,
+            mint: provider_bond.mint,
+            remaining_locked: provider_bond.locked_bond,
+            pending_claims: provider_bond.active_claims,
+            residual: provider_bond.available_bond()?,
+            timestamp,
+        }),
+        CollateralTransition::Unchanged => {}
+    }
+    Ok(())
+}
@@ -1,11 +1,25 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer, Mint};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use solana_instructions_sysvar as instructions_sysvar;
 
 mod state;
 mod errors;
+mod ed25519;
+mod mint_extensions;
+mod events;
+mod tombstone;
+mod settlement;
+mod batch;
+mod migration;
 
 use state::*;
 use errors::*;
+use events::*;
+use tombstone::CLAIM_TOMBSTONE_SEED;
+use settlement::{
+    raise_payout, release_escrow_to_provider, settle_dispute, settle_timed_out_claim,
+    transfer_from_vault, unlock_claim, ClaimPayout, CrankTip, EscrowRelease,
+};
 
 declare_id!("DMahL9qujZiirzLXKFvJxHhsNxG9uXh1yi1EnUCYgH7w");
 
@@ -19,112 +33,451 @@ pub mod x402_insurance {
         platform_penalty_rate: u16,
         default_timeout: u64,
         liquidation_grace_period: u64,
+        default_min_bond: u64,
     ) -> Result<()> {
         let config = &mut ctx.accounts.config;
-        config.platform_treasury = ctx.accounts.platform_treasury.key();
-        config.platform_penalty_rate = platform_penalty_rate;
-        config.default_timeout = default_timeout;
-        config.liquidation_grace_period = liquidation_grace_period;
-        config.authority = ctx.accounts.authority.key();
-        config.bump = ctx.bumps.config;
-
-        msg!("Insurance protocol initialized with penalty rate: {}bps, grace period: {}s",
-            platform_penalty_rate, liquidation_grace_period);
+        config.set_inner(InsuranceConfig::new(
+            ctx.accounts.platform_treasury.key(),
+            ctx.accounts.authority.key(),
+            platform_penalty_rate,
+            default_timeout,
+            liquidation_grace_period,
+            default_min_bond,
+            ctx.bumps.config,
+        ));
+        config.validate()?;
+
+        emit!(ConfigUpdated::from_config(config, Clock::get()?.unix_timestamp));
+        Ok(())
+    }
+
+    /// Authority updates protocol parameters
+    pub fn update_config(ctx: Context<UpdateConfig>, params: UpdateConfigParams) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        if let Some(platform_treasury) = params.platform_treasury {
+            config.platform_treasury = platform_treasury;
+        }
+        if let Some(platform_penalty_rate) = params.platform_penalty_rate {
+            config.platform_penalty_rate = platform_penalty_rate;
+        }
+        if let Some(coverage_multiplier_bps) = params.coverage_multiplier_bps {
+            config.coverage_multiplier_bps = coverage_multiplier_bps;
+        }
+        if let Some(default_timeout) = params.default_timeout {
+            config.default_timeout = default_timeout;
+        }
+        if let Some(liquidation_grace_period) = params.liquidation_grace_period {
+            config.liquidation_grace_period = liquidation_grace_period;
+        }
+        if let Some(default_min_bond) = params.default_min_bond {
+            config.default_min_bond = default_min_bond;
+        }
+        if let Some(dispute_window) = params.dispute_window {
+            config.dispute_window = dispute_window;
+        }
+        if let Some(arbiter) = params.arbiter {
+            config.arbiter = arbiter;
+        }
+        if let Some(crank_delay) = params.crank_delay {
+            config.crank_delay = crank_delay;
+        }
+        if let Some(crank_tip_bps) = params.crank_tip_bps {
+            config.crank_tip_bps = crank_tip_bps;
+        }
+        if let Some(liquidation_bonus_bps) = params.liquidation_bonus_bps {
+            config.liquidation_bonus_bps = liquidation_bonus_bps;
+        }
+        if let Some(max_liquidation_bonus_bps) = params.max_liquidation_bonus_bps {
+            config.max_liquidation_bonus_bps = max_liquidation_bonus_bps;
+        }
+        if let Some(liquidation_bonus_ramp) = params.liquidation_bonus_ramp {
+            config.liquidation_bonus_ramp = liquidation_bonus_ramp;
+        }
+        if let Some(reinstatement_bond_bps) = params.reinstatement_bond_bps {
+            config.reinstatement_bond_bps = reinstatement_bond_bps;
+        }
+        if let Some(reinstatement_cooldown) = params.reinstatement_cooldown {
+            config.reinstatement_cooldown = reinstatement_cooldown;
+        }
+        if let Some(reinstatement_requires_approval) = params.reinstatement_requires_approval {
+            config.reinstatement_requires_approval = reinstatement_requires_approval;
+        }
+        if let Some(liquidation_penalty_bps) = params.liquidation_penalty_bps {
+            config.liquidation_penalty_bps = liquidation_penalty_bps;
+        }
+        if let Some(dispute_loss_penalty_bps) = params.dispute_loss_penalty_bps {
+            config.dispute_loss_penalty_bps = dispute_loss_penalty_bps;
+        }
+        if let Some(invalid_signature_penalty_bps) = params.invalid_signature_penalty_bps {
+            config.invalid_signature_penalty_bps = invalid_signature_penalty_bps;
+        }
+
+        config.validate()?;
+
+        emit!(ConfigUpdated::from_config(config, Clock::get()?.unix_timestamp));
+        Ok(())
+    }
+
+    /// Authority proposes a new authority (first step of the handover)
+    ///
+    /// Proposing `Pubkey::default()` cancels a pending handover.
+    pub fn propose_authority(ctx: Context<UpdateConfig>, new_authority: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.pending_authority = new_authority;
+
+        emit!(AuthorityProposed {
+            authority: config.authority,
+            pending_authority: new_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Pending authority accepts the handover (second step)
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous_authority = config.authority;
+        config.authority = config.pending_authority;
+        config.pending_authority = Pubkey::default();
+
+        emit!(AuthorityTransferred {
+            previous_authority,
+            new_authority: config.authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Authority pauses or unpauses the protocol
+    ///
+    /// While paused, `purchase_insurance`, `deposit_bond`, `reinstate_provider`
+    /// and `liquidate_provider` are rejected; confirmations, claims and withdrawals
+    /// of unlocked bond keep working. Unpausing restarts every liquidation
+    /// grace period.
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let clock = Clock::get()?;
+        if config.paused && !paused {
+            config.unpaused_at = clock.unix_timestamp;
+        }
+        config.paused = paused;
+
+        emit!(ProtocolPauseUpdated {
+            authority: config.authority,
+            paused,
+            timestamp: clock.unix_timestamp,
+        });
         Ok(())
     }
 
     /// Provider deposits bond into the protocol
     pub fn deposit_bond(ctx: Context<DepositBond>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.config.paused, InsuranceError::ProtocolPaused);
+
         let provider_bond = &mut ctx.accounts.provider_bond;
 
         // Initialize fields if this is the first deposit
         if provider_bond.provider == Pubkey::default() {
-            provider_bond.provider = ctx.accounts.provider.key();
-            provider_bond.bump = ctx.bumps.provider_bond;
-            provider_bond.locked_bond = 0;
-            provider_bond.min_bond = 0;
-            provider_bond.is_liquidated = false;
-            provider_bond.undercollateralized_since = 0;
+            provider_bond.open(
+                ctx.accounts.provider.key(),
+                ctx.accounts.mint.key(),
+                ctx.accounts.config.default_min_bond,
+                ctx.bumps.provider_bond,
+            );
+        }
+
+        // The first deposit for a mint also opens its escrow ledger
+        let escrow_ledger = &mut ctx.accounts.escrow_ledger;
+        if escrow_ledger.mint == Pubkey::default() {
+            escrow_ledger.open(ctx.accounts.mint.key(), ctx.bumps.escrow_ledger);
         }
 
+        // Deposits into a liquidated bond would only be swept; use `reinstate_provider`
+        require!(!provider_bond.is_liquidated, InsuranceError::ProviderLiquidated);
+
+        mint_extensions::check_supported_mint(&ctx.accounts.mint)?;
+
         // Transfer tokens from provider to vault
-        let cpi_accounts = Transfer {
+        let vault_balance_before = ctx.accounts.vault.amount;
+        let cpi_accounts = TransferChecked {
             from: ctx.accounts.provider_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
             to: ctx.accounts.vault.to_account_info(),
             authority: ctx.accounts.provider.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        // Credit only what actually arrived (Token-2022 transfer fees are withheld in the vault)
+        ctx.accounts.vault.reload()?;
+        let received = ctx.accounts.vault.amount
+            .checked_sub(vault_balance_before)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
 
         // Update bond account
         provider_bond.total_bond = provider_bond.total_bond
-            .checked_add(amount)
+            .checked_add(received)
             .ok_or(InsuranceError::ArithmeticOverflow)?;
 
-        // Check if provider is now above min_bond, reset undercollateralized timestamp
-        let available_bond = provider_bond.total_bond
-            .checked_sub(provider_bond.locked_bond)
-            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        // Start the grace period if still below min_bond, or reset it if restored
+        let clock = Clock::get()?;
+        let transition = provider_bond.update_collateral_status(clock.unix_timestamp)?;
+
+        emit!(BondDeposited {
+            provider: provider_bond.provider,
+            mint: provider_bond.mint,
+            amount,
+            received,
+            total_bond: provider_bond.total_bond,
+            locked_bond: provider_bond.locked_bond,
+            timestamp: clock.unix_timestamp,
+        });
+        emit_collateral_transition(provider_bond, transition, clock.unix_timestamp)?;
+        Ok(())
+    }
 
-        if available_bond >= provider_bond.min_bond && provider_bond.undercollateralized_since > 0 {
-            provider_bond.undercollateralized_since = 0;
-            msg!("Provider {} restored to healthy collateral", ctx.accounts.provider.key());
+    /// Authority sets a provider's minimum bond and re-evaluates its collateral health
+    pub fn set_min_bond(ctx: Context<SetMinBond>, min_bond: u64) -> Result<()> {
+        let provider_bond = &mut ctx.accounts.provider_bond;
+        let previous_min_bond = provider_bond.min_bond;
+        provider_bond.min_bond = min_bond;
+
+        let clock = Clock::get()?;
+        let transition = provider_bond.update_collateral_status(clock.unix_timestamp)?;
+
+        emit!(MinBondUpdated {
+            provider: provider_bond.provider,
+            mint: provider_bond.mint,
+            previous_min_bond,
+            min_bond,
+            timestamp: clock.unix_timestamp,
+        });
+        emit_collateral_transition(provider_bond, transition, clock.unix_timestamp)?;
+        Ok(())
+    }
+
+    /// Provider overrides the coverage multiplier applied to its new insurances
+    ///
+    /// Pass 0 to fall back to the config default. Outstanding claims keep the
+    /// amounts snapshotted at purchase.
+    pub fn set_coverage_multiplier(
+        ctx: Context<SetCoverageMultiplier>,
+        coverage_multiplier_bps: u16,
+    ) -> Result<()> {
+        if coverage_multiplier_bps > 0 {
+            ctx.accounts.config.validate_coverage_multiplier(coverage_multiplier_bps)?;
+        }
+
+        let provider_bond = &mut ctx.accounts.provider_bond;
+        provider_bond.coverage_multiplier_bps = coverage_multiplier_bps;
+
+        emit!(CoverageMultiplierUpdated {
+            provider: provider_bond.provider,
+            mint: provider_bond.mint,
+            coverage_multiplier_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Provider requires (or stops requiring) a signed quote on every purchase
+    pub fn set_quote_required(ctx: Context<SetQuoteRequired>, requires_quote: bool) -> Result<()> {
+        let provider_bond = &mut ctx.accounts.provider_bond;
+        provider_bond.requires_quote = requires_quote;
+
+        emit!(QuoteRequirementUpdated {
+            provider: provider_bond.provider,
+            mint: provider_bond.mint,
+            requires_quote,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Provider sets the limits `purchase_insurance` enforces on its claims
+    pub fn set_provider_policy(
+        ctx: Context<SetProviderPolicy>,
+        params: ProviderPolicyParams,
+    ) -> Result<()> {
+        let policy = &mut ctx.accounts.provider_policy;
+        policy.provider = ctx.accounts.provider.key();
+        policy.max_payment_amount = params.max_payment_amount;
+        policy.min_timeout_seconds = params.min_timeout_seconds;
+        policy.max_timeout_seconds = params.max_timeout_seconds;
+        policy.max_concurrent_claims = params.max_concurrent_claims;
+        policy.max_locked_exposure = params.max_locked_exposure;
+        policy.allowed_mints = params.allowed_mints;
+        policy.paused = params.paused;
+        policy.arbiter = params.arbiter;
+        policy.payout_schedule = params.payout_schedule;
+        policy.escrow_payments = params.escrow_payments;
+        policy.bump = ctx.bumps.provider_policy;
+        policy.validate()?;
+
+        emit!(ProviderPolicyUpdated {
+            provider: policy.provider,
+            max_payment_amount: policy.max_payment_amount,
+            min_timeout_seconds: policy.min_timeout_seconds,
+            max_timeout_seconds: policy.max_timeout_seconds,
+            max_concurrent_claims: policy.max_concurrent_claims,
+            max_locked_exposure: policy.max_locked_exposure,
+            allowed_mints: policy.allowed_mints.clone(),
+            paused: policy.paused,
+            arbiter: policy.arbiter,
+            payout_schedule: policy.payout_schedule.clone(),
+            escrow_payments: policy.escrow_payments,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Provider stops (or resumes) accepting new insurance without withdrawing
+    ///
+    /// Creates a policy with no limits if the provider has not set one yet.
+    pub fn set_provider_paused(ctx: Context<SetProviderPolicy>, paused: bool) -> Result<()> {
+        let policy = &mut ctx.accounts.provider_policy;
+        if policy.provider == Pubkey::default() {
+            policy.provider = ctx.accounts.provider.key();
+            policy.bump = ctx.bumps.provider_policy;
         }
+        policy.paused = paused;
 
-        msg!("Provider {} deposited {} tokens", ctx.accounts.provider.key(), amount);
+        emit!(ProviderPauseUpdated {
+            provider: policy.provider,
+            paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 
     /// Client purchases insurance (zero fee!)
+    ///
+    /// `request_commitment` must be derived from the provider's 402 `challenge`
+    /// for this client (see `InsuranceClaim::request_commitment_for`), and the
+    /// claim PDA is scoped to client and provider, so nobody else can occupy it.
+    ///
+    /// With a `quote`, the transaction must include an Ed25519 program
+    /// instruction immediately before this one carrying the provider's signature
+    /// over `ed25519::payment_quote_message`. Providers that set `requires_quote`
+    /// only accept quoted purchases.
     pub fn purchase_insurance(
         ctx: Context<PurchaseInsurance>,
         request_commitment: [u8; 32],
+        challenge: [u8; 32],
         payment_amount: u64,
         timeout_minutes: u64,
+        quote: Option<PaymentQuote>,
     ) -> Result<()> {
         let provider_bond = &mut ctx.accounts.provider_bond;
         let claim = &mut ctx.accounts.claim;
         let config = &ctx.accounts.config;
 
+        require!(!config.paused, InsuranceError::ProtocolPaused);
+
         // Check provider not liquidated
         require!(!provider_bond.is_liquidated, InsuranceError::ProviderLiquidated);
 
-        // Calculate locked amount (payment_amount * 1.02)
-        let locked_amount = payment_amount
-            .checked_mul(102)
-            .and_then(|v| v.checked_div(100))
-            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        // Bind the commitment to the provider's challenge and this client
+        require!(
+            request_commitment == InsuranceClaim::request_commitment_for(
+                &ctx.accounts.provider.key(),
+                &ctx.accounts.client.key(),
+                &challenge,
+            ),
+            InsuranceError::InvalidRequestCommitment
+        );
+
+        // A closed claim leaves a tombstone so its commitment cannot be replayed
+        require!(
+            !tombstone::is_tombstoned(&ctx.accounts.claim_tombstone),
+            InsuranceError::CommitmentAlreadyUsed
+        );
+
+        let clock = Clock::get()?;
+        let timeout_seconds = config.claim_timeout(timeout_minutes)?;
+
+        // Check the provider priced this request before locking any bond
+        match &quote {
+            Some(quote) => {
+                let message = ed25519::payment_quote_message(
+                    &request_commitment,
+                    &ctx.accounts.mint.key(),
+                    quote.price,
+                    quote.max_timeout_seconds,
+                    quote.expiry_slot,
+                );
+                ed25519::verify_preceding_signature(
+                    &ctx.accounts.instructions_sysvar,
+                    &ctx.accounts.provider.key(),
+                    &message,
+                    None,
+                )?;
+                require!(payment_amount == quote.price, InsuranceError::QuoteMismatch);
+                require!(
+                    timeout_seconds <= quote.max_timeout_seconds,
+                    InsuranceError::QuoteMismatch
+                );
+                require!(clock.slot <= quote.expiry_slot, InsuranceError::QuoteExpired);
+            }
+            None => require!(!provider_bond.requires_quote, InsuranceError::QuoteRequired),
+        }
+
+        // Snapshot the locked amount, timeout payout and dispute terms
+        let policy = ProviderPolicy::load(&ctx.accounts.provider_policy)?;
+        let terms = ClaimTerms::new(config, provider_bond, policy.as_ref(), payment_amount)?;
+        let locked_amount = terms.locked_amount;
 
         // Check sufficient available bond
-        let available_bond = provider_bond.total_bond
-            .checked_sub(provider_bond.locked_bond)
-            .ok_or(InsuranceError::InsufficientBond)?;
+        require!(
+            provider_bond.available_bond()? >= locked_amount,
+            InsuranceError::InsufficientBond
+        );
 
-        require!(available_bond >= locked_amount, InsuranceError::InsufficientBond);
+        // Enforce the provider's coverage policy, if it has set one
+        if let Some(policy) = &policy {
+            policy.check_purchase(provider_bond, payment_amount, timeout_seconds, locked_amount)?;
+        }
 
-        // Transfer payment from client directly to provider (x402 payment)
-        let cpi_accounts = Transfer {
+        // Transfer payment from client directly to provider (x402 payment),
+        // or into the vault when the provider escrows payments until confirmation
+        let payee = if terms.escrow_payments {
+            ctx.accounts.vault.to_account_info()
+        } else {
+            ctx.accounts.provider_token_account.to_account_info()
+        };
+        let vault_balance_before = ctx.accounts.vault.amount;
+        let cpi_accounts = TransferChecked {
             from: ctx.accounts.client_token_account.to_account_info(),
-            to: ctx.accounts.provider_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: payee,
             authority: ctx.accounts.client.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, payment_amount)?;
+        token_interface::transfer_checked(cpi_ctx, payment_amount, ctx.accounts.mint.decimals)?;
+
+        // Escrow what actually arrived (net of any Token-2022 transfer fee)
+        let escrow_amount = if terms.escrow_payments {
+            ctx.accounts.vault.reload()?;
+            ctx.accounts.vault.amount
+                .checked_sub(vault_balance_before)
+                .ok_or(InsuranceError::ArithmeticOverflow)?
+        } else {
+            0
+        };
+        ctx.accounts.escrow_ledger.hold(escrow_amount)?;
 
         // Lock the bond (insurance guarantee)
         provider_bond.locked_bond = provider_bond.locked_bond
             .checked_add(locked_amount)
             .ok_or(InsuranceError::ArithmeticOverflow)?;
+        provider_bond.active_claims = provider_bond.active_claims
+            .checked_add(1)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
 
         // Calculate deadline
-        let clock = Clock::get()?;
-        let timeout_seconds = if timeout_minutes > 0 {
-            timeout_minutes * 60
-        } else {
-            config.default_timeout
-        };
         let deadline = clock.unix_timestamp
             .checked_add(timeout_seconds as i64)
             .ok_or(InsuranceError::ArithmeticOverflow)?;
@@ -133,77 +486,545 @@ pub mod x402_insurance {
         claim.request_commitment = request_commitment;
         claim.client = ctx.accounts.client.key();
         claim.provider = ctx.accounts.provider.key();
+        claim.mint = ctx.accounts.mint.key();
+        claim.payer = ctx.accounts.client.key();
         claim.payment_amount = payment_amount;
         claim.locked_amount = locked_amount;
+        claim.refund_amount = terms.refund_amount;
+        claim.penalty_amount = terms.penalty_amount;
         claim.deadline = deadline;
         claim.status = ClaimStatus::Pending;
+        claim.arbiter = terms.arbiter;
+        claim.dispute_window = terms.dispute_window;
+        claim.dispute_deadline = 0;
+        claim.created_at = clock.unix_timestamp;
+        claim.payout_schedule = terms.payout_schedule;
+        claim.payout_bps = 0;
+        claim.escrow_amount = escrow_amount;
         claim.bump = ctx.bumps.claim;
 
-        msg!(
-            "Insurance purchased: client={}, provider={}, amount={}, locked={}, deadline={}",
-            ctx.accounts.client.key(),
-            ctx.accounts.provider.key(),
+        emit!(InsurancePurchased {
+            claim: claim.key(),
+            request_commitment,
+            challenge,
+            client: claim.client,
+            provider: claim.provider,
+            mint: claim.mint,
             payment_amount,
             locked_amount,
-            deadline
+            refund_amount: claim.refund_amount,
+            penalty_amount: claim.penalty_amount,
+            deadline,
+            quoted: quote.is_some(),
+            arbiter: claim.arbiter,
+            escrow_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Client purchases insurance for several requests to one provider at once
+    ///
+    /// `remaining_accounts` holds a writable (claim, claim_tombstone) pair per
+    /// item, in order, where the claim is the item's uninitialized scoped claim
+    /// PDA. All payments go out in one transfer and the bond is locked once for
+    /// the batch; each claim still emits its own `InsurancePurchased`. Quoted
+    /// purchases are not supported in a batch.
+    pub fn purchase_insurance_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseInsuranceBatch<'info>>,
+        items: Vec<BatchPurchaseItem>,
+        timeout_minutes: u64,
+    ) -> Result<()> {
+        let provider_bond = &mut ctx.accounts.provider_bond;
+        let config = &ctx.accounts.config;
+
+        require!(!config.paused, InsuranceError::ProtocolPaused);
+        require!(!provider_bond.is_liquidated, InsuranceError::ProviderLiquidated);
+        require!(!provider_bond.requires_quote, InsuranceError::QuoteRequired);
+        require!(
+            !items.is_empty() && ctx.remaining_accounts.len() == items.len() * 2,
+            InsuranceError::InvalidBatch
         );
 
+        let clock = Clock::get()?;
+        let timeout_seconds = config.claim_timeout(timeout_minutes)?;
+        let deadline = clock.unix_timestamp
+            .checked_add(timeout_seconds as i64)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+
+        let client = ctx.accounts.client.key();
+        let provider = ctx.accounts.provider.key();
+        let mint = ctx.accounts.mint.key();
+        let policy = ProviderPolicy::load(&ctx.accounts.provider_policy)?;
+
+        let mut total_payment: u64 = 0;
+        let mut claims = Vec::with_capacity(items.len());
+        for (item, accounts) in items.iter().zip(ctx.remaining_accounts.chunks(2)) {
+            require!(
+                item.request_commitment
+                    == InsuranceClaim::request_commitment_for(&provider, &client, &item.challenge),
+                InsuranceError::InvalidRequestCommitment
+            );
+            let bump = batch::check_new_claim(
+                &accounts[0],
+                &accounts[1],
+                &client,
+                &provider,
+                &item.request_commitment,
+            )?;
+
+            let terms = ClaimTerms::new(config, provider_bond, policy.as_ref(), item.payment_amount)?;
+            require!(
+                provider_bond.available_bond()? >= terms.locked_amount,
+                InsuranceError::InsufficientBond
+            );
+            if let Some(policy) = &policy {
+                policy.check_purchase(
+                    provider_bond,
+                    item.payment_amount,
+                    timeout_seconds,
+                    terms.locked_amount,
+                )?;
+            }
+
+            // Count each claim against the bond so limits apply to the batch as a whole
+            provider_bond.locked_bond = provider_bond.locked_bond
+                .checked_add(terms.locked_amount)
+                .ok_or(InsuranceError::ArithmeticOverflow)?;
+            provider_bond.active_claims = provider_bond.active_claims
+                .checked_add(1)
+                .ok_or(InsuranceError::ArithmeticOverflow)?;
+            total_payment = total_payment
+                .checked_add(item.payment_amount)
+                .ok_or(InsuranceError::ArithmeticOverflow)?;
+
+            claims.push(InsuranceClaim {
+                request_commitment: item.request_commitment,
+                client,
+                provider,
+                mint,
+                payer: client,
+                payment_amount: item.payment_amount,
+                locked_amount: terms.locked_amount,
+                refund_amount: terms.refund_amount,
+                penalty_amount: terms.penalty_amount,
+                deadline,
+                status: ClaimStatus::Pending,
+                arbiter: terms.arbiter,
+                dispute_window: terms.dispute_window,
+                dispute_deadline: 0,
+                created_at: clock.unix_timestamp,
+                payout_schedule: terms.payout_schedule,
+                payout_bps: 0,
+                escrow_amount: 0,
+                bump,
+            });
+        }
+
+        if policy.as_ref().is_some_and(|policy| policy.escrow_payments) {
+            // Escrow each payment separately so transfer fees are attributed per claim
+            for claim in claims.iter_mut() {
+                let vault_balance_before = ctx.accounts.vault.amount;
+                let cpi_accounts = TransferChecked {
+                    from: ctx.accounts.client_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.client.to_account_info(),
+                };
+                let cpi_program = ctx.accounts.token_program.to_account_info();
+                let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+                token_interface::transfer_checked(cpi_ctx, claim.payment_amount, ctx.accounts.mint.decimals)?;
+
+                ctx.accounts.vault.reload()?;
+                claim.escrow_amount = ctx.accounts.vault.amount
+                    .checked_sub(vault_balance_before)
+                    .ok_or(InsuranceError::ArithmeticOverflow)?;
+                ctx.accounts.escrow_ledger.hold(claim.escrow_amount)?;
+            }
+        } else {
+            // Transfer all payments from client directly to provider in one go
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.client_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.provider_token_account.to_account_info(),
+                authority: ctx.accounts.client.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            token_interface::transfer_checked(cpi_ctx, total_payment, ctx.accounts.mint.decimals)?;
+        }
+
+        let client_info = ctx.accounts.client.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        for ((claim, item), accounts) in claims.iter().zip(&items).zip(ctx.remaining_accounts.chunks(2)) {
+            batch::create_claim_account(&accounts[0], claim, &client_info, &system_program)?;
+
+            emit!(InsurancePurchased {
+                claim: accounts[0].key(),
+                request_commitment: claim.request_commitment,
+                challenge: item.challenge,
+                client,
+                provider,
+                mint,
+                payment_amount: claim.payment_amount,
+                locked_amount: claim.locked_amount,
+                refund_amount: claim.refund_amount,
+                penalty_amount: claim.penalty_amount,
+                deadline,
+                quoted: false,
+                arbiter: claim.arbiter,
+                escrow_amount: claim.escrow_amount,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
         Ok(())
     }
 
     /// Provider confirms service delivery with Ed25519 signature
+    ///
+    /// The transaction must include an Ed25519 program instruction immediately
+    /// before this one, verifying `signature` by the claim's provider over the
+    /// canonical delivery proof message (see `ed25519::delivery_proof_message`).
+    ///
+    /// `delivered_fraction_bps` below 10000 reports a partial delivery. The
+    /// client is paid the larger of the undelivered share and the snapshotted
+    /// latency tier's share of the timeout payout; the rest of the bond unlocks.
+    ///
+    /// With `close_claim` set the settled claim is closed in the same
+    /// instruction and its rent returned to the payer.
     pub fn confirm_service(
         ctx: Context<ConfirmService>,
         _request_commitment: [u8; 32],
         signature: [u8; 64],
+        delivered_fraction_bps: u16,
+        close_claim: bool,
     ) -> Result<()> {
+        let claim_key = ctx.accounts.claim.key();
         let claim = &mut ctx.accounts.claim;
         let provider_bond = &mut ctx.accounts.provider_bond;
 
         // Check status
         require!(claim.status == ClaimStatus::Pending, InsuranceError::AlreadyConfirmed);
+        require!(
+            delivered_fraction_bps as u64 <= BPS_DENOMINATOR,
+            InsuranceError::InvalidDeliveredFraction
+        );
+
+        // Verify the provider's Ed25519 delivery proof via instruction introspection
+        let message = ed25519::delivery_proof_message(
+            &claim_key,
+            &claim.request_commitment,
+            claim.payment_amount,
+            claim.deadline,
+        );
+        ed25519::verify_preceding_signature(
+            &ctx.accounts.instructions_sysvar,
+            &claim.provider,
+            &message,
+            Some(&signature),
+        )?;
+
+        // Provider already received payment directly from client during purchase_insurance,
+        // so only a partial or late delivery pays anything out of the bond
+        let clock = Clock::get()?;
+        let latency_seconds = clock.unix_timestamp
+            .saturating_sub(claim.created_at)
+            .max(0) as u64;
+        let undelivered_bps = (BPS_DENOMINATOR as u16) - delivered_fraction_bps;
+        let payout_bps = undelivered_bps
+            .max(PayoutTier::payout_bps_for(&claim.payout_schedule, latency_seconds));
+
+        let receipt = raise_payout(
+            claim,
+            provider_bond,
+            &mut ClaimPayout {
+                vault: &ctx.accounts.vault,
+                vault_bump: ctx.bumps.vault,
+                client_token_account: &mut ctx.accounts.client_token_account,
+                platform_treasury_token_account: &ctx.accounts.platform_treasury_token_account,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                crank_tip: None,
+            },
+            payout_bps,
+        )?;
+
+        // An escrowed payment returns the same share to the client now
+        let mut escrow = EscrowRelease {
+            vault: &ctx.accounts.vault,
+            vault_bump: ctx.bumps.vault,
+            mint: &ctx.accounts.mint,
+            token_program: &ctx.accounts.token_program,
+            escrow_ledger: &mut ctx.accounts.escrow_ledger,
+        };
+        let escrow_refunded = escrow.refund_share(claim, &ctx.accounts.client_token_account, payout_bps)?;
+
+        // With a dispute window the remaining bond and escrow stay put until `release_bond`
+        let (unlocked_amount, escrow_released) = if claim.dispute_window > 0 {
+            claim.dispute_deadline = clock.unix_timestamp
+                .checked_add(claim.dispute_window as i64)
+                .ok_or(InsuranceError::ArithmeticOverflow)?;
+            (0, 0)
+        } else {
+            let escrow_released = claim.escrow_amount;
+            escrow.release(claim, &ctx.accounts.provider_token_account, escrow_released)?;
+            (unlock_claim(claim, provider_bond)?, escrow_released)
+        };
+        record_slash(provider_bond, SlashReason::Timeout, receipt.penalty_amount, clock.unix_timestamp)?;
+        let transition = provider_bond.update_collateral_status(clock.unix_timestamp)?;
+
+        // Update status
+        claim.status = ClaimStatus::Confirmed;
+
+        emit!(ServiceConfirmed {
+            claim: claim_key,
+            request_commitment: claim.request_commitment,
+            client: claim.client,
+            provider: claim.provider,
+            confirmed_by: claim.provider,
+            delivered_fraction_bps,
+            payout_bps,
+            refund_amount: receipt.refund_amount,
+            client_received: receipt.client_received,
+            penalty_amount: receipt.penalty_amount,
+            escrow_refunded,
+            escrow_released,
+            unlocked_amount,
+            dispute_deadline: claim.dispute_deadline,
+            timestamp: clock.unix_timestamp,
+        });
+        emit_collateral_transition(provider_bond, transition, clock.unix_timestamp)?;
+
+        if close_claim {
+            tombstone::close_requested_claim(
+                &mut ctx.accounts.claim,
+                ctx.accounts.claim_tombstone.as_ref(),
+                ctx.bumps.claim_tombstone,
+                ctx.accounts.payer.as_ref(),
+                ctx.accounts.system_program.as_ref(),
+            )?;
+        }
 
-        // Verify Ed25519 signature
-        // In production, use solana_program::ed25519_program for verification
-        // For now, we trust the provider signature (simplified for MVP)
-        // TODO: Implement full Ed25519 verification using instruction introspection
+        Ok(())
+    }
 
-        // In a production implementation, you would:
-        // 1. Create an Ed25519 instruction with the signature
-        // 2. Verify it in the same transaction
-        // 3. Check the signer matches the provider
+    /// Provider confirms delivery of several claims of one client at once
+    ///
+    /// `remaining_accounts` holds the writable claim PDAs, in item order. The
+    /// transaction must include one Ed25519 program instruction immediately
+    /// before this one with one signature per item, in item order, by the
+    /// provider over that claim's delivery proof message. Partial and late
+    /// payouts, dispute windows and per-claim events work as in `confirm_service`.
+    pub fn confirm_service_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ConfirmServiceBatch<'info>>,
+        items: Vec<BatchConfirmItem>,
+    ) -> Result<()> {
+        require!(
+            !items.is_empty() && ctx.remaining_accounts.len() == items.len(),
+            InsuranceError::InvalidBatch
+        );
 
-        msg!("Verifying signature: {:?}", signature);
+        // Every claim needs its own verified delivery proof, as in `confirm_service`
+        let signatures = ed25519::load_preceding_signatures(&ctx.accounts.instructions_sysvar)?;
+        require!(
+            signatures.len() == items.len(),
+            InsuranceError::MalformedEd25519Instruction
+        );
 
-        // Provider already received payment directly from client during purchase_insurance
-        // No need to transfer again, just unlock the bond
+        let clock = Clock::get()?;
+        let provider = ctx.accounts.provider.key();
+        let mint = ctx.accounts.mint.key();
+        let client = ctx.accounts.client_token_account.owner;
+
+        for ((item, claim_info), verified) in items.iter().zip(ctx.remaining_accounts).zip(&signatures) {
+            require!(claim_info.is_writable, InsuranceError::InvalidBatch);
+            let mut claim = Account::<InsuranceClaim>::try_from(claim_info)?;
+            require!(
+                claim.request_commitment == item.request_commitment
+                    && claim.has_canonical_address(&claim_info.key()),
+                InsuranceError::InvalidClaimAddress
+            );
+            require_keys_eq!(claim.provider, provider, InsuranceError::InvalidClaimAddress);
+            require_keys_eq!(claim.mint, mint, InsuranceError::MintMismatch);
+            require_keys_eq!(claim.client, client, InsuranceError::InvalidTokenAccountOwner);
+            require!(claim.status == ClaimStatus::Pending, InsuranceError::AlreadyConfirmed);
+            require!(
+                item.delivered_fraction_bps as u64 <= BPS_DENOMINATOR,
+                InsuranceError::InvalidDeliveredFraction
+            );
 
-        // Unlock the bond
-        provider_bond.locked_bond = provider_bond.locked_bond
-            .checked_sub(claim.locked_amount)
-            .ok_or(InsuranceError::ArithmeticOverflow)?;
+            let message = ed25519::delivery_proof_message(
+                &claim_info.key(),
+                &claim.request_commitment,
+                claim.payment_amount,
+                claim.deadline,
+            );
+            require!(
+                verified.signer == provider && verified.message == message,
+                InsuranceError::InvalidSignature
+            );
+
+            let latency_seconds = clock.unix_timestamp
+                .saturating_sub(claim.created_at)
+                .max(0) as u64;
+            let undelivered_bps = (BPS_DENOMINATOR as u16) - item.delivered_fraction_bps;
+            let payout_bps = undelivered_bps
+                .max(PayoutTier::payout_bps_for(&claim.payout_schedule, latency_seconds));
+
+            let receipt = raise_payout(
+                &mut claim,
+                &mut ctx.accounts.provider_bond,
+                &mut ClaimPayout {
+                    vault: &ctx.accounts.vault,
+                    vault_bump: ctx.bumps.vault,
+                    client_token_account: &mut ctx.accounts.client_token_account,
+                    platform_treasury_token_account: &ctx.accounts.platform_treasury_token_account,
+                    mint: &ctx.accounts.mint,
+                    token_program: &ctx.accounts.token_program,
+                    crank_tip: None,
+                },
+                payout_bps,
+            )?;
+
+            let mut escrow = EscrowRelease {
+                vault: &ctx.accounts.vault,
+                vault_bump: ctx.bumps.vault,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                escrow_ledger: &mut ctx.accounts.escrow_ledger,
+            };
+            let escrow_refunded =
+                escrow.refund_share(&mut claim, &ctx.accounts.client_token_account, payout_bps)?;
+
+            let (unlocked_amount, escrow_released) = if claim.dispute_window > 0 {
+                claim.dispute_deadline = clock.unix_timestamp
+                    .checked_add(claim.dispute_window as i64)
+                    .ok_or(InsuranceError::ArithmeticOverflow)?;
+                (0, 0)
+            } else {
+                let escrow_released = claim.escrow_amount;
+                escrow.release(&mut claim, &ctx.accounts.provider_token_account, escrow_released)?;
+                (unlock_claim(&claim, &mut ctx.accounts.provider_bond)?, escrow_released)
+            };
+            claim.status = ClaimStatus::Confirmed;
+            record_slash(
+                &mut ctx.accounts.provider_bond,
+                SlashReason::Timeout,
+                receipt.penalty_amount,
+                clock.unix_timestamp,
+            )?;
+
+            emit!(ServiceConfirmed {
+                claim: claim_info.key(),
+                request_commitment: claim.request_commitment,
+                client: claim.client,
+                provider: claim.provider,
+                confirmed_by: claim.provider,
+                delivered_fraction_bps: item.delivered_fraction_bps,
+                payout_bps,
+                refund_amount: receipt.refund_amount,
+                client_received: receipt.client_received,
+                penalty_amount: receipt.penalty_amount,
+                escrow_refunded,
+                escrow_released,
+                unlocked_amount,
+                dispute_deadline: claim.dispute_deadline,
+                timestamp: clock.unix_timestamp,
+            });
+            claim.exit(&crate::ID)?;
+        }
+
+        let provider_bond = &mut ctx.accounts.provider_bond;
+        let transition = provider_bond.update_collateral_status(clock.unix_timestamp)?;
+        emit_collateral_transition(provider_bond, transition, clock.unix_timestamp)?;
+        Ok(())
+    }
+
+    /// Client acknowledges delivery, confirming the claim and unlocking the bond
+    ///
+    /// Either the client signs this instruction directly, or anyone submits it
+    /// together with a preceding Ed25519 instruction carrying the client's
+    /// signature over the canonical receipt (see `ed25519::delivery_receipt_message`).
+    pub fn acknowledge_delivery(
+        ctx: Context<AcknowledgeDelivery>,
+        _request_commitment: [u8; 32],
+    ) -> Result<()> {
+        let claim_key = ctx.accounts.claim.key();
+        let claim = &mut ctx.accounts.claim;
+        let provider_bond = &mut ctx.accounts.provider_bond;
+
+        // Check status
+        require!(claim.status == ClaimStatus::Pending, InsuranceError::AlreadyConfirmed);
+
+        // A client signature on the transaction is itself the receipt
+        if ctx.accounts.submitter.key() != claim.client {
+            let message = ed25519::delivery_receipt_message(
+                &claim_key,
+                &claim.request_commitment,
+                claim.payment_amount,
+                claim.deadline,
+            );
+            ed25519::verify_preceding_signature(
+                &ctx.accounts.instructions_sysvar,
+                &claim.client,
+                &message,
+                None,
+            )?;
+        }
+
+        // Unlock the bond and release any escrow; acknowledging waives any
+        // partial or late payout
+        let unlocked_amount = unlock_claim(claim, provider_bond)?;
+        let escrow_released = release_escrow_to_provider(
+            claim,
+            ctx.accounts.vault.as_ref(),
+            ctx.bumps.vault,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            ctx.accounts.escrow_ledger.as_deref_mut(),
+            ctx.accounts.provider_token_account.as_ref(),
+        )?;
+        let clock = Clock::get()?;
+        let transition = provider_bond.update_collateral_status(clock.unix_timestamp)?;
 
         // Update status
         claim.status = ClaimStatus::Confirmed;
 
-        msg!(
-            "Service confirmed for request {:?}, unlocked {} tokens (provider already received {})",
-            claim.request_commitment,
-            claim.locked_amount,
-            claim.payment_amount
-        );
+        emit!(ServiceConfirmed {
+            claim: claim_key,
+            request_commitment: claim.request_commitment,
+            client: claim.client,
+            provider: claim.provider,
+            confirmed_by: claim.client,
+            delivered_fraction_bps: BPS_DENOMINATOR as u16,
+            payout_bps: 0,
+            refund_amount: 0,
+            client_received: 0,
+            penalty_amount: 0,
+            escrow_refunded: 0,
+            escrow_released,
+            unlocked_amount,
+            dispute_deadline: 0,
+            timestamp: clock.unix_timestamp,
+        });
+        emit_collateral_transition(provider_bond, transition, clock.unix_timestamp)?;
 
         Ok(())
     }
 
     /// Client claims insurance after timeout
+    ///
+    /// With `close_claim` set the settled claim is closed in the same
+    /// instruction and its rent returned to the payer.
     pub fn claim_insurance(
         ctx: Context<ClaimInsurance>,
         _request_commitment: [u8; 32],
+        close_claim: bool,
     ) -> Result<()> {
         let claim = &mut ctx.accounts.claim;
         let provider_bond = &mut ctx.accounts.provider_bond;
-        let config = &ctx.accounts.config;
 
         // Check status
         require!(claim.status == ClaimStatus::Pending, InsuranceError::CannotClaimAfterConfirmation);
@@ -212,146 +1033,542 @@ pub mod x402_insurance {
         let clock = Clock::get()?;
         require!(clock.unix_timestamp >= claim.deadline, InsuranceError::DeadlineNotReached);
 
-        // Calculate amounts
-        // Client gets refund of original payment (1 USDC) from provider's bond
-        let refund_to_client = claim.payment_amount;
+        settle_timed_out_claim(
+            claim,
+            provider_bond,
+            ClaimPayout {
+                vault: &ctx.accounts.vault,
+                vault_bump: ctx.bumps.vault,
+                client_token_account: &mut ctx.accounts.client_token_account,
+                platform_treasury_token_account: &ctx.accounts.platform_treasury_token_account,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                crank_tip: None,
+            },
+            &mut ctx.accounts.escrow_ledger,
+            clock.unix_timestamp,
+        )?;
+
+        if close_claim {
+            tombstone::close_requested_claim(
+                &mut ctx.accounts.claim,
+                ctx.accounts.claim_tombstone.as_ref(),
+                ctx.bumps.claim_tombstone,
+                ctx.accounts.payer.as_ref(),
+                ctx.accounts.system_program.as_ref(),
+            )?;
+        }
 
-        // Platform gets penalty (0.02 USDC = 2% of payment) from provider's bond
-        let penalty = claim.payment_amount
-            .checked_mul(config.platform_penalty_rate as u64)
-            .and_then(|v| v.checked_div(10000))
-            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Anyone settles a timed-out claim on the client's behalf
+    ///
+    /// Available `crank_delay` seconds after the deadline so the provider's bond
+    /// does not stay locked when the client never claims. Pays out exactly like
+    /// `claim_insurance`, except the cranker receives `crank_tip_bps` of the
+    /// penalty in place of the treasury.
+    pub fn settle_expired_claim(
+        ctx: Context<SettleExpiredClaim>,
+        _request_commitment: [u8; 32],
+        close_claim: bool,
+    ) -> Result<()> {
+        let claim = &mut ctx.accounts.claim;
+        let provider_bond = &mut ctx.accounts.provider_bond;
+
+        require!(claim.status == ClaimStatus::Pending, InsuranceError::CannotClaimAfterConfirmation);
 
-        // Total deduction from provider bond = refund + penalty = 1.02 USDC
-        let bond_deduction = refund_to_client
-            .checked_add(penalty)
+        let clock = Clock::get()?;
+        let settle_after = claim.deadline
+            .checked_add(ctx.accounts.config.crank_delay as i64)
             .ok_or(InsuranceError::ArithmeticOverflow)?;
+        require!(clock.unix_timestamp >= settle_after, InsuranceError::DeadlineNotReached);
+
+        let receipt = settle_timed_out_claim(
+            claim,
+            provider_bond,
+            ClaimPayout {
+                vault: &ctx.accounts.vault,
+                vault_bump: ctx.bumps.vault,
+                client_token_account: &mut ctx.accounts.client_token_account,
+                platform_treasury_token_account: &ctx.accounts.platform_treasury_token_account,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                crank_tip: Some(CrankTip {
+                    token_account: &ctx.accounts.cranker_token_account,
+                    tip_bps: ctx.accounts.config.crank_tip_bps,
+                }),
+            },
+            &mut ctx.accounts.escrow_ledger,
+            clock.unix_timestamp,
+        )?;
+        emit!(ExpiredClaimSettled {
+            claim: claim.key(),
+            request_commitment: claim.request_commitment,
+            cranker: ctx.accounts.cranker.key(),
+            tip_amount: receipt.tip_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if close_claim {
+            tombstone::close_requested_claim(
+                &mut ctx.accounts.claim,
+                ctx.accounts.claim_tombstone.as_ref(),
+                ctx.bumps.claim_tombstone,
+                ctx.accounts.payer.as_ref(),
+                ctx.accounts.system_program.as_ref(),
+            )?;
+        }
+
+        Ok(())
+    }
 
-        // Verify bond_deduction equals locked_amount (should be 1.02x payment)
+    /// Client disputes a provider-confirmed claim within the dispute window
+    pub fn dispute_claim(ctx: Context<DisputeClaim>, _request_commitment: [u8; 32]) -> Result<()> {
+        let claim = &mut ctx.accounts.claim;
+
+        require!(claim.arbiter != Pubkey::default(), InsuranceError::DisputesDisabled);
+        require!(claim.status == ClaimStatus::Confirmed, InsuranceError::DisputeWindowClosed);
+
+        // A zero deadline means the bond was already released (or the client acknowledged)
+        let clock = Clock::get()?;
         require!(
-            bond_deduction == claim.locked_amount,
-            InsuranceError::ArithmeticOverflow
+            claim.dispute_deadline > 0 && clock.unix_timestamp < claim.dispute_deadline,
+            InsuranceError::DisputeWindowClosed
         );
 
-        // Deduct full locked amount from provider bond
-        provider_bond.total_bond = provider_bond.total_bond
-            .checked_sub(bond_deduction)
-            .ok_or(InsuranceError::InsufficientBond)?;
-
-        // Unlock the bond
-        provider_bond.locked_bond = provider_bond.locked_bond
-            .checked_sub(claim.locked_amount)
+        // The arbiter gets another dispute window to rule before the default applies
+        claim.status = ClaimStatus::Disputed;
+        claim.dispute_deadline = clock.unix_timestamp
+            .checked_add(claim.dispute_window as i64)
             .ok_or(InsuranceError::ArithmeticOverflow)?;
 
-        // Transfer refund to client from vault (provider's bond)
-        let seeds = &[
-            b"vault".as_ref(),
-            &[ctx.bumps.vault],
-        ];
-        let signer = &[&seeds[..]];
-
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.vault.to_account_info(),
-            to: ctx.accounts.client_token_account.to_account_info(),
-            authority: ctx.accounts.vault.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, refund_to_client)?;
-
-        // Transfer penalty from vault (provider's bond) to platform treasury
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.vault.to_account_info(),
-            to: ctx.accounts.platform_treasury_token_account.to_account_info(),
-            authority: ctx.accounts.vault.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, penalty)?;
+        emit!(ClaimDisputed {
+            claim: claim.key(),
+            request_commitment: claim.request_commitment,
+            client: claim.client,
+            provider: claim.provider,
+            arbiter: claim.arbiter,
+            ruling_deadline: claim.dispute_deadline,
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
 
-        // Check if provider is now below min_bond after claim
-        let available_after = provider_bond.total_bond
-            .checked_sub(provider_bond.locked_bond)
-            .ok_or(InsuranceError::ArithmeticOverflow)?;
+    /// Arbiter resolves a disputed claim before its ruling deadline
+    ///
+    /// See `settle_dispute` for what each ruling pays out.
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        _request_commitment: [u8; 32],
+        ruling: DisputeRuling,
+    ) -> Result<()> {
+        require!(ctx.accounts.claim.status == ClaimStatus::Disputed, InsuranceError::NotDisputed);
+        require!(
+            Clock::get()?.unix_timestamp < ctx.accounts.claim.dispute_deadline,
+            InsuranceError::RulingDeadlinePassed
+        );
 
-        if available_after < provider_bond.min_bond && provider_bond.undercollateralized_since == 0 {
-            let clock = Clock::get()?;
-            provider_bond.undercollateralized_since = clock.unix_timestamp;
-            msg!("Provider {} is now undercollateralized after claim (available: {}, min: {})",
-                claim.provider,
-                available_after,
-                provider_bond.min_bond
-            );
-        }
+        settle_dispute(
+            &mut ctx.accounts.claim,
+            &mut ctx.accounts.provider_bond,
+            ClaimPayout {
+                vault: &ctx.accounts.vault,
+                vault_bump: ctx.bumps.vault,
+                client_token_account: &mut ctx.accounts.client_token_account,
+                platform_treasury_token_account: &ctx.accounts.platform_treasury_token_account,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                crank_tip: None,
+            },
+            &mut ctx.accounts.escrow_ledger,
+            &ctx.accounts.provider_token_account,
+            &ctx.accounts.config,
+            ruling,
+        )
+    }
 
-        // Update status
-        claim.status = ClaimStatus::Claimed;
+    /// Anyone applies the default ruling to a dispute the arbiter left past its deadline
+    ///
+    /// The default is `DisputeRuling::DEFAULT` (for the client), so a claim
+    /// can never stay disputed with its bond locked forever.
+    pub fn expire_dispute(ctx: Context<ExpireDispute>, _request_commitment: [u8; 32]) -> Result<()> {
+        require!(ctx.accounts.claim.status == ClaimStatus::Disputed, InsuranceError::NotDisputed);
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.claim.dispute_deadline,
+            InsuranceError::RulingDeadlineNotReached
+        );
+
+        settle_dispute(
+            &mut ctx.accounts.claim,
+            &mut ctx.accounts.provider_bond,
+            ClaimPayout {
+                vault: &ctx.accounts.vault,
+                vault_bump: ctx.bumps.vault,
+                client_token_account: &mut ctx.accounts.client_token_account,
+                platform_treasury_token_account: &ctx.accounts.platform_treasury_token_account,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                crank_tip: None,
+            },
+            &mut ctx.accounts.escrow_ledger,
+            &ctx.accounts.provider_token_account,
+            &ctx.accounts.config,
+            DisputeRuling::DEFAULT,
+        )
+    }
+
+    /// Unlock a confirmed claim's bond once its dispute window closed undisputed
+    ///
+    /// Permissionless, so the provider or any keeper can release the bond.
+    pub fn release_bond(ctx: Context<ReleaseBond>, _request_commitment: [u8; 32]) -> Result<()> {
+        let claim = &mut ctx.accounts.claim;
+        let provider_bond = &mut ctx.accounts.provider_bond;
+
+        require!(
+            claim.status == ClaimStatus::Confirmed && claim.dispute_deadline > 0,
+            InsuranceError::ClaimNotSettled
+        );
 
-        msg!(
-            "Insurance claimed: client refunded {} (original payment from bond), platform received {} penalty (from bond), total bond deducted {}",
-            refund_to_client,
-            penalty,
-            bond_deduction
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= claim.dispute_deadline,
+            InsuranceError::DisputeWindowOpen
         );
 
+        let unlocked_amount = unlock_claim(claim, provider_bond)?;
+        let escrow_released = release_escrow_to_provider(
+            claim,
+            ctx.accounts.vault.as_ref(),
+            ctx.bumps.vault,
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            ctx.accounts.escrow_ledger.as_deref_mut(),
+            ctx.accounts.provider_token_account.as_ref(),
+        )?;
+        let transition = provider_bond.update_collateral_status(clock.unix_timestamp)?;
+        claim.dispute_deadline = 0;
+
+        emit!(BondReleased {
+            claim: claim.key(),
+            request_commitment: claim.request_commitment,
+            provider: claim.provider,
+            unlocked_amount,
+            escrow_released,
+            timestamp: clock.unix_timestamp,
+        });
+        emit_collateral_transition(provider_bond, transition, clock.unix_timestamp)?;
         Ok(())
     }
 
-    /// Provider withdraws available bond
-    pub fn withdraw_bond(ctx: Context<WithdrawBond>, amount: u64) -> Result<()> {
-        let provider_bond = &mut ctx.accounts.provider_bond;
+    /// Close a confirmed or claimed claim and return its rent to the payer
+    ///
+    /// Permissionless: rent always goes to the recorded payer. A tombstone PDA
+    /// is left behind so the request commitment cannot be reused.
+    pub fn close_claim(ctx: Context<CloseClaim>, _request_commitment: [u8; 32]) -> Result<()> {
+        tombstone::close_claim(
+            &mut ctx.accounts.claim,
+            &ctx.accounts.claim_tombstone,
+            ctx.bumps.claim_tombstone,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )
+    }
 
-        // Check available bond
-        let available = provider_bond.total_bond
-            .checked_sub(provider_bond.locked_bond)
-            .ok_or(InsuranceError::InsufficientAvailableBond)?;
+    /// Client opens a prepaid session with a provider
+    ///
+    /// The whole budget (`price_per_request * max_requests`) is paid to the
+    /// provider up front and the bond is locked once for it, instead of one
+    /// claim account per request.
+    pub fn open_session(
+        ctx: Context<OpenSession>,
+        nonce: u64,
+        price_per_request: u64,
+        max_requests: u64,
+        duration_minutes: u64,
+    ) -> Result<()> {
+        let provider_bond = &mut ctx.accounts.provider_bond;
+        let session = &mut ctx.accounts.session;
+        let config = &ctx.accounts.config;
 
-        require!(available >= amount, InsuranceError::InsufficientAvailableBond);
+        require!(!config.paused, InsuranceError::ProtocolPaused);
+        require!(!provider_bond.is_liquidated, InsuranceError::ProviderLiquidated);
+        require!(!provider_bond.requires_quote, InsuranceError::QuoteRequired);
 
-        // Deduct from total bond
-        provider_bond.total_bond = provider_bond.total_bond
-            .checked_sub(amount)
+        let duration_seconds = duration_minutes
+            .checked_mul(60)
+            .ok_or(InsuranceError::InvalidSession)?;
+        require!(
+            price_per_request > 0
+                && max_requests > 0
+                && (InsuranceConfig::MIN_TIMEOUT..=Session::MAX_DURATION).contains(&duration_seconds),
+            InsuranceError::InvalidSession
+        );
+        let budget = price_per_request
+            .checked_mul(max_requests)
             .ok_or(InsuranceError::ArithmeticOverflow)?;
 
-        // Transfer tokens from vault to provider
-        let seeds = &[
-            b"vault".as_ref(),
-            &[ctx.bumps.vault],
-        ];
-        let signer = &[&seeds[..]];
-
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.vault.to_account_info(),
+        // Lock the bond for the whole budget, as if it were one large claim
+        let coverage_multiplier_bps = provider_bond.effective_coverage_multiplier_bps(config);
+        config.validate_coverage_multiplier(coverage_multiplier_bps)?;
+        let locked_amount = apply_bps(budget, coverage_multiplier_bps)?;
+        require!(
+            provider_bond.available_bond()? >= locked_amount,
+            InsuranceError::InsufficientBond
+        );
+        if let Some(policy) = ProviderPolicy::load(&ctx.accounts.provider_policy)? {
+            policy.check_session(provider_bond, price_per_request, locked_amount)?;
+        }
+
+        // Prepay the budget directly to the provider
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.client_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
             to: ctx.accounts.provider_token_account.to_account_info(),
-            authority: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.client.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, amount)?;
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token_interface::transfer_checked(cpi_ctx, budget, ctx.accounts.mint.decimals)?;
 
-        // Check if provider is now below min_bond after withdrawal
-        let available_after = provider_bond.total_bond
-            .checked_sub(provider_bond.locked_bond)
+        provider_bond.locked_bond = provider_bond.locked_bond
+            .checked_add(locked_amount)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        provider_bond.active_claims = provider_bond.active_claims
+            .checked_add(1)
             .ok_or(InsuranceError::ArithmeticOverflow)?;
 
-        if available_after < provider_bond.min_bond && provider_bond.undercollateralized_since == 0 {
-            let clock = Clock::get()?;
-            provider_bond.undercollateralized_since = clock.unix_timestamp;
-            msg!("Provider {} is now undercollateralized (available: {}, min: {})",
-                ctx.accounts.provider.key(),
-                available_after,
-                provider_bond.min_bond
+        let clock = Clock::get()?;
+        session.client = ctx.accounts.client.key();
+        session.provider = ctx.accounts.provider.key();
+        session.mint = ctx.accounts.mint.key();
+        session.nonce = nonce;
+        session.price_per_request = price_per_request;
+        session.max_requests = max_requests;
+        session.request_count = 0;
+        session.served_count = 0;
+        session.request_hash = [0; 32];
+        session.locked_amount = locked_amount;
+        session.penalty_rate_bps = config.slash_rate_bps(SlashReason::Timeout);
+        session.expires_at = clock.unix_timestamp
+            .checked_add(duration_seconds as i64)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        session.bump = ctx.bumps.session;
+
+        emit!(SessionOpened {
+            session: session.key(),
+            client: session.client,
+            provider: session.provider,
+            mint: session.mint,
+            price_per_request,
+            max_requests,
+            locked_amount,
+            expires_at: session.expires_at,
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Client records requests made under a session
+    ///
+    /// Each commitment is folded into the session's rolling `request_hash`
+    /// rather than getting its own account.
+    pub fn record_session_requests(
+        ctx: Context<RecordSessionRequests>,
+        request_commitments: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let session = &mut ctx.accounts.session;
+
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp < session.expires_at, InsuranceError::SessionExpired);
+
+        for request_commitment in &request_commitments {
+            session.record_request(request_commitment)?;
+        }
+
+        emit!(SessionRequestsRecorded {
+            session: session.key(),
+            recorded: request_commitments.len() as u64,
+            request_count: session.request_count,
+            request_hash: session.request_hash,
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Provider confirms it served the session's requests up to `served_count`
+    ///
+    /// The transaction must include an Ed25519 program instruction immediately
+    /// before this one, verifying the client's receipt for `served_count` (see
+    /// `ed25519::session_receipt_message`), since the served count decides
+    /// what the client is refunded on close. The receipt also proves the
+    /// requests happened, so `request_count` is raised to `served_count` when
+    /// the client has not recorded them.
+    pub fn confirm_session_service(
+        ctx: Context<ConfirmSessionService>,
+        served_count: u64,
+    ) -> Result<()> {
+        let session_key = ctx.accounts.session.key();
+        let session = &mut ctx.accounts.session;
+
+        require!(
+            served_count > session.served_count && served_count <= session.max_requests,
+            InsuranceError::InvalidServedCount
+        );
+
+        let message = ed25519::session_receipt_message(&session_key, served_count);
+        ed25519::verify_preceding_signature(
+            &ctx.accounts.instructions_sysvar,
+            &session.client,
+            &message,
+            None,
+        )?;
+
+        session.served_count = served_count;
+        session.request_count = session.request_count.max(served_count);
+
+        emit!(SessionServed {
+            session: session.key(),
+            served_count,
+            request_count: session.request_count,
+            request_hash: session.request_hash,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Settle and close a session
+    ///
+    /// Every request the provider did not serve, including budget never used,
+    /// is refunded to the client from the locked bond; requests recorded but
+    /// not served also pay the penalty to the treasury. The rest of the bond
+    /// unlocks. The provider may close at any time, the client once the
+    /// session has expired.
+    pub fn close_session(ctx: Context<CloseSession>) -> Result<()> {
+        let session = &ctx.accounts.session;
+        let closer = ctx.accounts.closer.key();
+
+        let clock = Clock::get()?;
+        if closer != session.provider {
+            require_keys_eq!(closer, session.client, InsuranceError::NotSessionParty);
+            require!(
+                clock.unix_timestamp >= session.expires_at,
+                InsuranceError::SessionNotExpired
             );
         }
 
-        msg!("Provider {} withdrew {} tokens", ctx.accounts.provider.key(), amount);
+        let (refund_amount, penalty_amount) = session.settlement()?;
+        let client_received = if refund_amount > 0 || penalty_amount > 0 {
+            ClaimPayout {
+                vault: &ctx.accounts.vault,
+                vault_bump: ctx.bumps.vault,
+                client_token_account: &mut ctx.accounts.client_token_account,
+                platform_treasury_token_account: &ctx.accounts.platform_treasury_token_account,
+                mint: &ctx.accounts.mint,
+                token_program: &ctx.accounts.token_program,
+                crank_tip: None,
+            }
+            .pay(refund_amount, penalty_amount)?
+            .client_received
+        } else {
+            0
+        };
+
+        let session = &ctx.accounts.session;
+        let provider_bond = &mut ctx.accounts.provider_bond;
+        let bond_deduction = refund_amount
+            .checked_add(penalty_amount)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        provider_bond.total_bond = provider_bond.total_bond
+            .checked_sub(bond_deduction)
+            .ok_or(InsuranceError::InsufficientBond)?;
+        provider_bond.locked_bond = provider_bond.locked_bond
+            .checked_sub(session.locked_amount)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        provider_bond.active_claims = provider_bond.active_claims
+            .checked_sub(1)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        record_slash(provider_bond, SlashReason::Timeout, penalty_amount, clock.unix_timestamp)?;
+        let transition = provider_bond.update_collateral_status(clock.unix_timestamp)?;
+
+        emit!(SessionClosed {
+            session: session.key(),
+            client: session.client,
+            provider: session.provider,
+            served_count: session.served_count,
+            request_count: session.request_count,
+            request_hash: session.request_hash,
+            refund_amount,
+            client_received,
+            penalty_amount,
+            unlocked_amount: session.locked_amount,
+            timestamp: clock.unix_timestamp,
+        });
+        emit_collateral_transition(provider_bond, transition, clock.unix_timestamp)?;
+        Ok(())
+    }
+
+    /// Provider withdraws available bond
+    pub fn withdraw_bond(ctx: Context<WithdrawBond>, amount: u64) -> Result<()> {
+        let provider_bond = &mut ctx.accounts.provider_bond;
+
+        // A liquidated provider's unlocked bond is swept, not withdrawn
+        require!(!provider_bond.is_liquidated, InsuranceError::ProviderLiquidated);
+
+        // Check available bond
+        let available = provider_bond.total_bond
+            .checked_sub(provider_bond.locked_bond)
+            .ok_or(InsuranceError::InsufficientAvailableBond)?;
+
+        require!(available >= amount, InsuranceError::InsufficientAvailableBond);
+
+        // Bond never comes out of the payments escrowed alongside it
+        ctx.accounts.escrow_ledger.check_bond_transfer(ctx.accounts.vault.amount, amount)?;
+
+        // Deduct from total bond
+        provider_bond.total_bond = provider_bond.total_bond
+            .checked_sub(amount)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+
+        // Transfer tokens from vault to provider
+        transfer_from_vault(
+            &ctx.accounts.vault,
+            ctx.bumps.vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.token_program,
+            ctx.accounts.provider_token_account.to_account_info(),
+            amount,
+        )?;
+
+        // Check if provider is now below min_bond after withdrawal
+        let clock = Clock::get()?;
+        let transition = provider_bond.update_collateral_status(clock.unix_timestamp)?;
+
+        emit!(BondWithdrawn {
+            provider: provider_bond.provider,
+            mint: provider_bond.mint,
+            amount,
+            total_bond: provider_bond.total_bond,
+            timestamp: clock.unix_timestamp,
+        });
+        emit_collateral_transition(provider_bond, transition, clock.unix_timestamp)?;
         Ok(())
     }
 
     /// Liquidate undercollateralized provider after grace period
+    ///
+    /// With a `liquidation_penalty_bps` only that share of `min_bond` is seized
+    /// and the provider keeps operating with a fresh grace period to restore
+    /// collateral. Without one, or once the penalty would take everything
+    /// available, all available bond is seized and the provider winds down.
+    ///
+    /// The liquidator receives a bonus out of the seized bond that rises the
+    /// longer the provider stays liquidatable; the treasury gets the rest.
+    /// Rejected while the protocol is paused, since providers cannot top up.
     pub fn liquidate_provider(ctx: Context<LiquidateProvider>) -> Result<()> {
         let provider_bond = &mut ctx.accounts.provider_bond;
         let config = &ctx.accounts.config;
+        require!(!config.paused, InsuranceError::ProtocolPaused);
 
         // Check not already liquidated
         require!(!provider_bond.is_liquidated, InsuranceError::ProviderLiquidated);
@@ -373,43 +1590,376 @@ pub mod x402_insurance {
             InsuranceError::ProviderNotUndercollateralized
         );
 
-        let grace_period_end = provider_bond.undercollateralized_since
-            .checked_add(config.liquidation_grace_period as i64)
-            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        let grace_period_end = config.grace_period_end(provider_bond.undercollateralized_since)?;
 
         require!(
             clock.unix_timestamp >= grace_period_end,
             InsuranceError::GracePeriodNotExpired
         );
 
-        // Transfer all remaining available bond to platform treasury
-        if available_bond > 0 {
-            let seeds = &[
-                b"vault".as_ref(),
-                &[ctx.bumps.vault],
-            ];
-            let signer = &[&seeds[..]];
-
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.vault.to_account_info(),
-                to: ctx.accounts.platform_treasury_token_account.to_account_info(),
-                authority: ctx.accounts.vault.to_account_info(),
-            };
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-            token::transfer(cpi_ctx, available_bond)?;
+        let penalty_bps = config.slash_rate_bps(SlashReason::Liquidation);
+        let seized_amount = if penalty_bps > 0 {
+            apply_bps(provider_bond.min_bond, penalty_bps)?.min(available_bond)
+        } else {
+            available_bond
+        };
+        let partial = seized_amount < available_bond;
+        ctx.accounts.escrow_ledger.check_bond_transfer(ctx.accounts.vault.amount, seized_amount)?;
+
+        // Split the seized bond between the liquidator and the treasury
+        let bonus_bps = config.liquidation_bonus_bps(clock.unix_timestamp - grace_period_end);
+        let liquidator_bonus = apply_bps(seized_amount, bonus_bps)?;
+        let treasury_amount = seized_amount
+            .checked_sub(liquidator_bonus)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+
+        for (to, amount) in [
+            (ctx.accounts.liquidator_token_account.to_account_info(), liquidator_bonus),
+            (ctx.accounts.platform_treasury_token_account.to_account_info(), treasury_amount),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            transfer_from_vault(
+                &ctx.accounts.vault,
+                ctx.bumps.vault,
+                &ctx.accounts.mint,
+                &ctx.accounts.token_program,
+                to,
+                amount,
+            )?;
+        }
+
+        if partial {
+            // Penalised but still operating; the next liquidation needs another grace period
+            provider_bond.total_bond = provider_bond.total_bond
+                .checked_sub(seized_amount)
+                .ok_or(InsuranceError::ArithmeticOverflow)?;
+            provider_bond.undercollateralized_since = clock.unix_timestamp;
+        } else {
+            // Mark provider as liquidated; pending claims still settle against the
+            // locked bond, and whatever they unlock is swept once none remain
+            provider_bond.is_liquidated = true;
+            provider_bond.liquidated_at = clock.unix_timestamp;
+            provider_bond.undercollateralized_since = 0;
+            provider_bond.total_bond = provider_bond.locked_bond; // Only locked bond remains
         }
+        record_slash(provider_bond, SlashReason::Liquidation, seized_amount, clock.unix_timestamp)?;
+
+        emit!(ProviderLiquidated {
+            provider: provider_bond.provider,
+            mint: provider_bond.mint,
+            liquidator: ctx.accounts.liquidator.key(),
+            partial,
+            seized_amount,
+            bonus_bps,
+            liquidator_bonus,
+            remaining_locked: provider_bond.locked_bond,
+            pending_claims: provider_bond.active_claims,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
 
-        // Mark provider as liquidated
-        provider_bond.is_liquidated = true;
-        provider_bond.total_bond = provider_bond.locked_bond; // Only locked bond remains
+    /// Anyone sweeps a liquidated provider's residual bond to the treasury
+    ///
+    /// Only once every pending claim and session has settled, so nothing the
+    /// wind-down still owes clients is taken.
+    pub fn sweep_liquidated_bond(ctx: Context<SweepLiquidatedBond>) -> Result<()> {
+        let provider_bond = &mut ctx.accounts.provider_bond;
 
-        msg!(
-            "Provider {} liquidated: {} tokens transferred to platform treasury",
-            provider_bond.provider,
-            available_bond
+        require!(provider_bond.is_liquidated, InsuranceError::ProviderNotLiquidated);
+        require!(
+            provider_bond.active_claims == 0 && provider_bond.locked_bond == 0,
+            InsuranceError::WindDownIncomplete
+        );
+
+        let amount = provider_bond.total_bond;
+        if amount > 0 {
+            ctx.accounts.escrow_ledger.check_bond_transfer(ctx.accounts.vault.amount, amount)?;
+            transfer_from_vault(
+                &ctx.accounts.vault,
+                ctx.bumps.vault,
+                &ctx.accounts.mint,
+                &ctx.accounts.token_program,
+                ctx.accounts.platform_treasury_token_account.to_account_info(),
+                amount,
+            )?;
+        }
+        provider_bond.total_bond = 0;
+
+        emit!(LiquidatedBondSwept {
+            provider: provider_bond.provider,
+            mint: provider_bond.mint,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Liquidated provider deposits a fresh bond and leaves liquidation
+    ///
+    /// Only after the wind-down is complete and swept and the cool-down has
+    /// passed. The deposit must cover `reinstatement_bond_bps` of `min_bond`,
+    /// and the authority must co-sign when the config requires approval.
+    pub fn reinstate_provider(ctx: Context<ReinstateProvider>, amount: u64) -> Result<()> {
+        let provider_bond = &mut ctx.accounts.provider_bond;
+        let config = &ctx.accounts.config;
+        require!(!config.paused, InsuranceError::ProtocolPaused);
+
+        require!(provider_bond.is_liquidated, InsuranceError::ProviderNotLiquidated);
+        require!(
+            provider_bond.active_claims == 0 && provider_bond.locked_bond == 0,
+            InsuranceError::WindDownIncomplete
+        );
+        require!(provider_bond.total_bond == 0, InsuranceError::ResidualNotSwept);
+
+        let clock = Clock::get()?;
+        let cooldown_end = provider_bond.liquidated_at
+            .checked_add(config.reinstatement_cooldown as i64)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        require!(clock.unix_timestamp >= cooldown_end, InsuranceError::ReinstatementCooldown);
+
+        let approved_by = match &ctx.accounts.authority {
+            Some(authority) => authority.key(),
+            None => {
+                require!(!config.reinstatement_requires_approval, InsuranceError::ReinstatementNotApproved);
+                Pubkey::default()
+            }
+        };
+
+        // Transfer tokens from provider to vault
+        let vault_balance_before = ctx.accounts.vault.amount;
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.provider_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.provider.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        ctx.accounts.vault.reload()?;
+        let received = ctx.accounts.vault.amount
+            .checked_sub(vault_balance_before)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        require!(
+            received >= apply_bps(provider_bond.min_bond, config.reinstatement_bond_bps)?,
+            InsuranceError::InsufficientReinstatementBond
+        );
+
+        // Start over with a clean liquidation record
+        provider_bond.total_bond = received;
+        provider_bond.is_liquidated = false;
+        provider_bond.liquidated_at = 0;
+        provider_bond.undercollateralized_since = 0;
+
+        emit!(ProviderReinstated {
+            provider: provider_bond.provider,
+            mint: provider_bond.mint,
+            amount,
+            received,
+            min_bond: provider_bond.min_bond,
+            approved_by,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Authority rewrites a first-release config in the current layout
+    ///
+    /// Parameters the first release did not have start switched off, as on
+    /// `initialize`. Run this before migrating claims and bonds.
+    pub fn migrate_legacy_config(ctx: Context<MigrateLegacyConfig>) -> Result<()> {
+        let config_info = ctx.accounts.config.to_account_info();
+        let legacy = migration::LegacyInsuranceConfig::load(&config_info)?;
+        require_keys_eq!(legacy.authority, ctx.accounts.authority.key(), InsuranceError::Unauthorized);
+
+        let config = InsuranceConfig::new(
+            legacy.platform_treasury,
+            legacy.authority,
+            legacy.platform_penalty_rate,
+            legacy.default_timeout,
+            legacy.liquidation_grace_period,
+            0,
+            legacy.bump,
+        );
+        config.validate()?;
+
+        migration::grow(
+            &config_info,
+            InsuranceConfig::LEN,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let mut data = config_info.try_borrow_mut_data()?;
+        config.try_serialize(&mut &mut data[..])?;
+
+        emit!(ConfigUpdated::from_config(&config, Clock::get()?.unix_timestamp));
+        Ok(())
+    }
+
+    /// Authority settles a first-release claim and closes it
+    ///
+    /// A pending claim must be past its deadline and pays out as a timeout did
+    /// in that release: the payment back to the client and the rest of the
+    /// locked bond to the treasury, both out of the legacy vault and bond.
+    /// Rent goes back to the client.
+    pub fn migrate_legacy_claim(
+        ctx: Context<MigrateLegacyClaim>,
+        _request_commitment: [u8; 32],
+    ) -> Result<()> {
+        let claim_info = ctx.accounts.legacy_claim.to_account_info();
+        let claim = migration::LegacyInsuranceClaim::load(&claim_info)?;
+        require_keys_eq!(ctx.accounts.client.key(), claim.client, InsuranceError::InvalidPayer);
+        require_keys_eq!(
+            ctx.accounts.client_token_account.owner,
+            claim.client,
+            InsuranceError::InvalidTokenAccountOwner
         );
 
+        let clock = Clock::get()?;
+        let (refund_amount, penalty_amount) = if claim.status == ClaimStatus::Pending {
+            require!(clock.unix_timestamp >= claim.deadline, InsuranceError::DeadlineNotReached);
+
+            let bond_info = ctx.accounts.legacy_provider_bond.to_account_info();
+            require_keys_eq!(
+                bond_info.key(),
+                migration::LegacyProviderBond::address(&claim.provider),
+                InsuranceError::InvalidLegacyAccount
+            );
+            let mut provider_bond = migration::LegacyProviderBond::load(&bond_info)?;
+
+            // The first release locked exactly the refund plus the penalty
+            let penalty_amount = claim.locked_amount
+                .checked_sub(claim.payment_amount)
+                .ok_or(InsuranceError::ArithmeticOverflow)?;
+            provider_bond.total_bond = provider_bond.total_bond
+                .checked_sub(claim.locked_amount)
+                .ok_or(InsuranceError::InsufficientBond)?;
+            provider_bond.locked_bond = provider_bond.locked_bond
+                .checked_sub(claim.locked_amount)
+                .ok_or(InsuranceError::ArithmeticOverflow)?;
+            provider_bond.store(&bond_info)?;
+
+            migration::transfer_from_legacy_vault(
+                &ctx.accounts.legacy_vault,
+                ctx.bumps.legacy_vault,
+                &ctx.accounts.mint,
+                &ctx.accounts.token_program,
+                ctx.accounts.client_token_account.to_account_info(),
+                claim.payment_amount,
+            )?;
+            if penalty_amount > 0 {
+                migration::transfer_from_legacy_vault(
+                    &ctx.accounts.legacy_vault,
+                    ctx.bumps.legacy_vault,
+                    &ctx.accounts.mint,
+                    &ctx.accounts.token_program,
+                    ctx.accounts.platform_treasury_token_account.to_account_info(),
+                    penalty_amount,
+                )?;
+            }
+            (claim.payment_amount, penalty_amount)
+        } else {
+            (0, 0)
+        };
+
+        migration::close(&claim_info, &ctx.accounts.client.to_account_info())?;
+
+        emit!(LegacyClaimMigrated {
+            claim: claim_info.key(),
+            request_commitment: claim.request_commitment,
+            client: claim.client,
+            provider: claim.provider,
+            refund_amount,
+            penalty_amount,
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Authority moves a first-release bond into its mint-keyed PDA
+    ///
+    /// The legacy vault held a single mint, so the whole bond moves to that
+    /// mint's vault and is credited to the provider's bond for it, keeping the
+    /// legacy `min_bond`. A bond liquidated under the first release goes to the
+    /// treasury instead. Migrate its pending claims first; rent goes back to
+    /// the provider.
+    pub fn migrate_legacy_bond(ctx: Context<MigrateLegacyBond>) -> Result<()> {
+        let bond_info = ctx.accounts.legacy_provider_bond.to_account_info();
+        let legacy = migration::LegacyProviderBond::load(&bond_info)?;
+        require!(legacy.locked_bond == 0, InsuranceError::LegacyClaimsPending);
+
+        let provider_bond = &mut ctx.accounts.provider_bond;
+        if provider_bond.provider == Pubkey::default() {
+            provider_bond.open(
+                legacy.provider,
+                ctx.accounts.mint.key(),
+                legacy.min_bond,
+                ctx.bumps.provider_bond,
+            );
+        }
+        let escrow_ledger = &mut ctx.accounts.escrow_ledger;
+        if escrow_ledger.mint == Pubkey::default() {
+            escrow_ledger.open(ctx.accounts.mint.key(), ctx.bumps.escrow_ledger);
+        }
+
+        let amount = legacy.total_bond;
+        let received = if legacy.is_liquidated {
+            if amount > 0 {
+                migration::transfer_from_legacy_vault(
+                    &ctx.accounts.legacy_vault,
+                    ctx.bumps.legacy_vault,
+                    &ctx.accounts.mint,
+                    &ctx.accounts.token_program,
+                    ctx.accounts.platform_treasury_token_account.to_account_info(),
+                    amount,
+                )?;
+            }
+            0
+        } else {
+            require!(!provider_bond.is_liquidated, InsuranceError::ProviderLiquidated);
+            let vault_balance_before = ctx.accounts.vault.amount;
+            if amount > 0 {
+                migration::transfer_from_legacy_vault(
+                    &ctx.accounts.legacy_vault,
+                    ctx.bumps.legacy_vault,
+                    &ctx.accounts.mint,
+                    &ctx.accounts.token_program,
+                    ctx.accounts.vault.to_account_info(),
+                    amount,
+                )?;
+            }
+            ctx.accounts.vault.reload()?;
+            ctx.accounts.vault.amount
+                .checked_sub(vault_balance_before)
+                .ok_or(InsuranceError::ArithmeticOverflow)?
+        };
+
+        provider_bond.total_bond = provider_bond.total_bond
+            .checked_add(received)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+
+        migration::close(&bond_info, &ctx.accounts.provider.to_account_info())?;
+
+        let clock = Clock::get()?;
+        let transition = provider_bond.update_collateral_status(clock.unix_timestamp)?;
+
+        emit!(LegacyBondMigrated {
+            provider: provider_bond.provider,
+            mint: provider_bond.mint,
+            amount,
+            received,
+            swept: legacy.is_liquidated,
+            total_bond: provider_bond.total_bond,
+            timestamp: clock.unix_timestamp,
+        });
+        emit_collateral_transition(provider_bond, transition, clock.unix_timestamp)?;
         Ok(())
     }
 }
@@ -438,13 +1988,42 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority @ InsuranceError::Unauthorized
+    )]
+    pub config: Account<'info, InsuranceConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_authority == new_authority.key() @ InsuranceError::NotPendingAuthority
+    )]
+    pub config: Account<'info, InsuranceConfig>,
+
+    pub new_authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct DepositBond<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, InsuranceConfig>,
+
     #[account(
         init_if_needed,
         payer = provider,
         space = ProviderBond::LEN,
-        seeds = [b"provider_bond", provider.key().as_ref()],
+        seeds = [b"provider_bond", provider.key().as_ref(), mint.key().as_ref()],
         bump
     )]
     pub provider_bond: Account<'info, ProviderBond>,
@@ -452,150 +2031,969 @@ pub struct DepositBond<'info> {
     #[account(mut)]
     pub provider: Signer<'info>,
 
-    #[account(mut)]
-    pub provider_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = provider_token_account.mint == mint.key() @ InsuranceError::MintMismatch,
+        constraint = provider_token_account.owner == provider.key() @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub provider_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub mint: Account<'info, Mint>,
+    pub mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         init_if_needed,
         payer = provider,
         token::mint = mint,
         token::authority = vault,
-        seeds = [b"vault"],
+        token::token_program = token_program,
+        seeds = [b"vault", mint.key().as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = provider,
+        space = EscrowLedger::LEN,
+        seeds = [b"escrow_ledger", mint.key().as_ref()],
         bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub escrow_ledger: Account<'info, EscrowLedger>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-#[instruction(request_commitment: [u8; 32])]
-pub struct PurchaseInsurance<'info> {
-    #[account(seeds = [b"config"], bump = config.bump)]
+pub struct SetMinBond<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority @ InsuranceError::Unauthorized
+    )]
     pub config: Account<'info, InsuranceConfig>,
 
     #[account(
         mut,
-        seeds = [b"provider_bond", provider.key().as_ref()],
+        seeds = [b"provider_bond", provider_bond.provider.as_ref(), provider_bond.mint.as_ref()],
         bump = provider_bond.bump
     )]
     pub provider_bond: Account<'info, ProviderBond>,
 
-    #[account(
-        init,
-        payer = client,
-        space = InsuranceClaim::LEN,
-        seeds = [b"claim", request_commitment.as_ref()],
-        bump
-    )]
-    pub claim: Account<'info, InsuranceClaim>,
-
-    #[account(mut)]
-    pub client: Signer<'info>,
-
-    #[account(mut)]
-    pub client_token_account: Account<'info, TokenAccount>,
-
-    /// CHECK: Provider address
-    pub provider: AccountInfo<'info>,
+    pub authority: Signer<'info>,
+}
 
-    #[account(mut)]
-    pub provider_token_account: Account<'info, TokenAccount>,
+#[derive(Accounts)]
+pub struct SetCoverageMultiplier<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, InsuranceConfig>,
 
     #[account(
         mut,
-        seeds = [b"vault"],
-        bump
+        seeds = [b"provider_bond", provider.key().as_ref(), provider_bond.mint.as_ref()],
+        bump = provider_bond.bump,
+        constraint = provider_bond.provider == provider.key()
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub provider_bond: Account<'info, ProviderBond>,
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    pub provider: Signer<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(request_commitment: [u8; 32])]
-pub struct ConfirmService<'info> {
-    #[account(
-        mut,
-        seeds = [b"claim", request_commitment.as_ref()],
-        bump = claim.bump,
-        constraint = claim.provider == provider.key()
-    )]
-    pub claim: Account<'info, InsuranceClaim>,
-
+pub struct SetQuoteRequired<'info> {
     #[account(
         mut,
-        seeds = [b"provider_bond", provider.key().as_ref()],
-        bump = provider_bond.bump
+        seeds = [b"provider_bond", provider.key().as_ref(), provider_bond.mint.as_ref()],
+        bump = provider_bond.bump,
+        constraint = provider_bond.provider == provider.key()
     )]
     pub provider_bond: Account<'info, ProviderBond>,
 
-    #[account(mut)]
     pub provider: Signer<'info>,
+}
 
-    #[account(mut)]
-    pub provider_token_account: Account<'info, TokenAccount>,
-
+#[derive(Accounts)]
+pub struct SetProviderPolicy<'info> {
     #[account(
-        mut,
-        seeds = [b"vault"],
+        init_if_needed,
+        payer = provider,
+        space = ProviderPolicy::LEN,
+        seeds = [b"provider_policy", provider.key().as_ref()],
         bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub provider_policy: Account<'info, ProviderPolicy>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 #[instruction(request_commitment: [u8; 32])]
-pub struct ClaimInsurance<'info> {
+pub struct PurchaseInsurance<'info> {
     #[account(seeds = [b"config"], bump = config.bump)]
     pub config: Account<'info, InsuranceConfig>,
 
     #[account(
         mut,
-        seeds = [b"claim", request_commitment.as_ref()],
-        bump = claim.bump,
-        constraint = claim.client == client.key()
-    )]
-    pub claim: Account<'info, InsuranceClaim>,
-
-    #[account(
-        mut,
-        seeds = [b"provider_bond", claim.provider.as_ref()],
+        seeds = [b"provider_bond", provider.key().as_ref(), mint.key().as_ref()],
         bump = provider_bond.bump
     )]
     pub provider_bond: Account<'info, ProviderBond>,
 
+    /// CHECK: Provider's coverage policy PDA, enforced only if the provider created one
     #[account(
-        mut,
-        seeds = [b"vault"],
+        seeds = [b"provider_policy", provider.key().as_ref()],
         bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub provider_policy: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = client,
+        space = InsuranceClaim::LEN,
+        seeds = [
+            b"claim",
+            client.key().as_ref(),
+            provider.key().as_ref(),
+            request_commitment.as_ref()
+        ],
+        bump
+    )]
+    pub claim: Account<'info, InsuranceClaim>,
+
+    /// CHECK: Tombstone PDA of the claim, only checked for prior closure
+    #[account(
+        seeds = [CLAIM_TOMBSTONE_SEED, claim.key().as_ref()],
+        bump
+    )]
+    pub claim_tombstone: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub client: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = client_token_account.mint == mint.key() @ InsuranceError::MintMismatch,
+        constraint = client_token_account.owner == client.key() @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub client_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Provider address
+    pub provider: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = provider_token_account.mint == mint.key() @ InsuranceError::MintMismatch,
+        constraint = provider_token_account.owner == provider.key() @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub provider_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", mint.key().as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow_ledger", mint.key().as_ref()],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
+
+    /// CHECK: Instructions sysvar, used to introspect the provider's quote signature
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PurchaseInsuranceBatch<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, InsuranceConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", provider.key().as_ref(), mint.key().as_ref()],
+        bump = provider_bond.bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    /// CHECK: Provider's coverage policy PDA, enforced only if the provider created one
+    #[account(
+        seeds = [b"provider_policy", provider.key().as_ref()],
+        bump
+    )]
+    pub provider_policy: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub client: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = client_token_account.mint == mint.key() @ InsuranceError::MintMismatch,
+        constraint = client_token_account.owner == client.key() @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub client_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Provider address
+    pub provider: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = provider_token_account.mint == mint.key() @ InsuranceError::MintMismatch,
+        constraint = provider_token_account.owner == provider.key() @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub provider_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Holds the payments when the provider escrows them
+    #[account(
+        mut,
+        seeds = [b"vault", mint.key().as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow_ledger", mint.key().as_ref()],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(request_commitment: [u8; 32])]
+pub struct ConfirmService<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, InsuranceConfig>,
+
+    #[account(
+        mut,
+        constraint = claim.request_commitment == request_commitment @ InsuranceError::InvalidClaimAddress,
+        constraint = claim.has_canonical_address(&claim.key()) @ InsuranceError::InvalidClaimAddress,
+        constraint = claim.provider == provider.key()
+    )]
+    pub claim: Account<'info, InsuranceClaim>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", provider.key().as_ref(), claim.mint.as_ref()],
+        bump = provider_bond.bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = provider_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = provider_token_account.owner == provider.key() @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub provider_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", claim.mint.as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow_ledger", claim.mint.as_ref()],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
+
+    /// Receives any partial or late delivery refund
+    #[account(
+        mut,
+        constraint = client_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = client_token_account.owner == claim.client @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub client_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = platform_treasury_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = platform_treasury_token_account.owner == config.platform_treasury @ InsuranceError::InvalidTreasuryAccount
+    )]
+    pub platform_treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = claim.mint @ InsuranceError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Instructions sysvar, used to introspect the Ed25519 verification instruction
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Receives the claim's rent when `close_claim` is set
+    #[account(mut, address = claim.payer @ InsuranceError::InvalidPayer)]
+    pub payer: Option<SystemAccount<'info>>,
+
+    /// CHECK: Tombstone PDA marking the claim as closed, created when `close_claim` is set
+    #[account(
+        mut,
+        seeds = [CLAIM_TOMBSTONE_SEED, claim.key().as_ref()],
+        bump
+    )]
+    pub claim_tombstone: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmServiceBatch<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, InsuranceConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", provider.key().as_ref(), mint.key().as_ref()],
+        bump = provider_bond.bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    pub provider: Signer<'info>,
+
+    /// Receives escrowed payments
+    #[account(
+        mut,
+        constraint = provider_token_account.mint == mint.key() @ InsuranceError::MintMismatch,
+        constraint = provider_token_account.owner == provider.key() @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub provider_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", mint.key().as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow_ledger", mint.key().as_ref()],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
+
+    /// Client of every claim in the batch; receives any partial or late delivery refund
+    #[account(
+        mut,
+        constraint = client_token_account.mint == mint.key() @ InsuranceError::MintMismatch
+    )]
+    pub client_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = platform_treasury_token_account.mint == mint.key() @ InsuranceError::MintMismatch,
+        constraint = platform_treasury_token_account.owner == config.platform_treasury @ InsuranceError::InvalidTreasuryAccount
+    )]
+    pub platform_treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Instructions sysvar, used to introspect the Ed25519 verification instruction
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(request_commitment: [u8; 32])]
+pub struct AcknowledgeDelivery<'info> {
+    #[account(
+        mut,
+        constraint = claim.request_commitment == request_commitment @ InsuranceError::InvalidClaimAddress,
+        constraint = claim.has_canonical_address(&claim.key()) @ InsuranceError::InvalidClaimAddress
+    )]
+    pub claim: Account<'info, InsuranceClaim>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", claim.provider.as_ref(), claim.mint.as_ref()],
+        bump = provider_bond.bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    /// Client, or anyone relaying a client-signed receipt
+    pub submitter: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, used to introspect the Ed25519 verification instruction
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+
+    /// Receives the escrowed payment, required only for escrowed claims
+    #[account(
+        mut,
+        constraint = provider_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = provider_token_account.owner == claim.provider @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub provider_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", claim.mint.as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow_ledger", claim.mint.as_ref()],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Option<Account<'info, EscrowLedger>>,
+
+    #[account(address = claim.mint @ InsuranceError::MintMismatch)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+#[instruction(request_commitment: [u8; 32])]
+pub struct ClaimInsurance<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, InsuranceConfig>,
+
+    #[account(
+        mut,
+        constraint = claim.request_commitment == request_commitment @ InsuranceError::InvalidClaimAddress,
+        constraint = claim.has_canonical_address(&claim.key()) @ InsuranceError::InvalidClaimAddress,
+        constraint = claim.client == client.key()
+    )]
+    pub claim: Account<'info, InsuranceClaim>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", claim.provider.as_ref(), claim.mint.as_ref()],
+        bump = provider_bond.bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", claim.mint.as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow_ledger", claim.mint.as_ref()],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
+
+    #[account(mut)]
+    pub client: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = client_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = client_token_account.owner == claim.client @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub client_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = platform_treasury_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = platform_treasury_token_account.owner == config.platform_treasury @ InsuranceError::InvalidTreasuryAccount
+    )]
+    pub platform_treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = claim.mint @ InsuranceError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Receives the claim's rent when `close_claim` is set
+    #[account(mut, address = claim.payer @ InsuranceError::InvalidPayer)]
+    pub payer: Option<SystemAccount<'info>>,
+
+    /// CHECK: Tombstone PDA marking the claim as closed, created when `close_claim` is set
+    #[account(
+        mut,
+        seeds = [CLAIM_TOMBSTONE_SEED, claim.key().as_ref()],
+        bump
+    )]
+    pub claim_tombstone: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+}
+
+#[derive(Accounts)]
+#[instruction(request_commitment: [u8; 32])]
+pub struct SettleExpiredClaim<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, InsuranceConfig>,
+
+    #[account(
+        mut,
+        constraint = claim.request_commitment == request_commitment @ InsuranceError::InvalidClaimAddress,
+        constraint = claim.has_canonical_address(&claim.key()) @ InsuranceError::InvalidClaimAddress
+    )]
+    pub claim: Account<'info, InsuranceClaim>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", claim.provider.as_ref(), claim.mint.as_ref()],
+        bump = provider_bond.bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", claim.mint.as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow_ledger", claim.mint.as_ref()],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
+
+    /// Anyone; receives the crank tip
+    pub cranker: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = cranker_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = cranker_token_account.owner == cranker.key() @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub cranker_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = client_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = client_token_account.owner == claim.client @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub client_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = platform_treasury_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = platform_treasury_token_account.owner == config.platform_treasury @ InsuranceError::InvalidTreasuryAccount
+    )]
+    pub platform_treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = claim.mint @ InsuranceError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Receives the claim's rent when `close_claim` is set
+    #[account(mut, address = claim.payer @ InsuranceError::InvalidPayer)]
+    pub payer: Option<SystemAccount<'info>>,
+
+    /// CHECK: Tombstone PDA marking the claim as closed, created when `close_claim` is set
+    #[account(
+        mut,
+        seeds = [CLAIM_TOMBSTONE_SEED, claim.key().as_ref()],
+        bump
+    )]
+    pub claim_tombstone: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+}
+
+#[derive(Accounts)]
+#[instruction(request_commitment: [u8; 32])]
+pub struct DisputeClaim<'info> {
+    #[account(
+        mut,
+        constraint = claim.request_commitment == request_commitment @ InsuranceError::InvalidClaimAddress,
+        constraint = claim.has_canonical_address(&claim.key()) @ InsuranceError::InvalidClaimAddress,
+        constraint = claim.client == client.key()
+    )]
+    pub claim: Account<'info, InsuranceClaim>,
+
+    pub client: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(request_commitment: [u8; 32])]
+pub struct ResolveDispute<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, InsuranceConfig>,
+
+    #[account(
+        mut,
+        constraint = claim.request_commitment == request_commitment @ InsuranceError::InvalidClaimAddress,
+        constraint = claim.has_canonical_address(&claim.key()) @ InsuranceError::InvalidClaimAddress,
+        constraint = claim.arbiter == arbiter.key() @ InsuranceError::NotArbiter
+    )]
+    pub claim: Account<'info, InsuranceClaim>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", claim.provider.as_ref(), claim.mint.as_ref()],
+        bump = provider_bond.bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", claim.mint.as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow_ledger", claim.mint.as_ref()],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
+
+    pub arbiter: Signer<'info>,
+
+    /// Receives the escrowed payment on a provider ruling
+    #[account(
+        mut,
+        constraint = provider_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = provider_token_account.owner == claim.provider @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub provider_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = client_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = client_token_account.owner == claim.client @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub client_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = platform_treasury_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = platform_treasury_token_account.owner == config.platform_treasury @ InsuranceError::InvalidTreasuryAccount
+    )]
+    pub platform_treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = claim.mint @ InsuranceError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(request_commitment: [u8; 32])]
+pub struct ExpireDispute<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, InsuranceConfig>,
+
+    #[account(
+        mut,
+        constraint = claim.request_commitment == request_commitment @ InsuranceError::InvalidClaimAddress,
+        constraint = claim.has_canonical_address(&claim.key()) @ InsuranceError::InvalidClaimAddress
+    )]
+    pub claim: Account<'info, InsuranceClaim>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", claim.provider.as_ref(), claim.mint.as_ref()],
+        bump = provider_bond.bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", claim.mint.as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow_ledger", claim.mint.as_ref()],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
+
+    /// Receives the escrowed payment on a provider ruling
+    #[account(
+        mut,
+        constraint = provider_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = provider_token_account.owner == claim.provider @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub provider_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = client_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = client_token_account.owner == claim.client @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub client_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = platform_treasury_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = platform_treasury_token_account.owner == config.platform_treasury @ InsuranceError::InvalidTreasuryAccount
+    )]
+    pub platform_treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = claim.mint @ InsuranceError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(request_commitment: [u8; 32])]
+pub struct ReleaseBond<'info> {
+    #[account(
+        mut,
+        constraint = claim.request_commitment == request_commitment @ InsuranceError::InvalidClaimAddress,
+        constraint = claim.has_canonical_address(&claim.key()) @ InsuranceError::InvalidClaimAddress
+    )]
+    pub claim: Account<'info, InsuranceClaim>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", claim.provider.as_ref(), claim.mint.as_ref()],
+        bump = provider_bond.bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    /// Receives the escrowed payment, required only for escrowed claims
+    #[account(
+        mut,
+        constraint = provider_token_account.mint == claim.mint @ InsuranceError::MintMismatch,
+        constraint = provider_token_account.owner == claim.provider @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub provider_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", claim.mint.as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow_ledger", claim.mint.as_ref()],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Option<Account<'info, EscrowLedger>>,
+
+    #[account(address = claim.mint @ InsuranceError::MintMismatch)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+#[instruction(request_commitment: [u8; 32])]
+pub struct CloseClaim<'info> {
+    #[account(
+        mut,
+        constraint = claim.request_commitment == request_commitment @ InsuranceError::InvalidClaimAddress,
+        constraint = claim.has_canonical_address(&claim.key()) @ InsuranceError::InvalidClaimAddress
+    )]
+    pub claim: Account<'info, InsuranceClaim>,
+
+    /// CHECK: Tombstone PDA marking the claim as closed, created here
+    #[account(
+        mut,
+        seeds = [CLAIM_TOMBSTONE_SEED, claim.key().as_ref()],
+        bump
+    )]
+    pub claim_tombstone: UncheckedAccount<'info>,
+
+    #[account(mut, address = claim.payer @ InsuranceError::InvalidPayer)]
+    pub payer: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct OpenSession<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, InsuranceConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", provider.key().as_ref(), mint.key().as_ref()],
+        bump = provider_bond.bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    /// CHECK: Provider's coverage policy PDA, enforced only if the provider created one
+    #[account(
+        seeds = [b"provider_policy", provider.key().as_ref()],
+        bump
+    )]
+    pub provider_policy: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = client,
+        space = Session::LEN,
+        seeds = [
+            b"session",
+            client.key().as_ref(),
+            provider.key().as_ref(),
+            nonce.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub session: Account<'info, Session>,
 
     #[account(mut)]
     pub client: Signer<'info>,
 
-    #[account(mut)]
-    pub client_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = client_token_account.mint == mint.key() @ InsuranceError::MintMismatch,
+        constraint = client_token_account.owner == client.key() @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub client_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub platform_treasury_token_account: Account<'info, TokenAccount>,
+    /// CHECK: Provider address
+    pub provider: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = provider_token_account.mint == mint.key() @ InsuranceError::MintMismatch,
+        constraint = provider_token_account.owner == provider.key() @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub provider_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordSessionRequests<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"session",
+            session.client.as_ref(),
+            session.provider.as_ref(),
+            session.nonce.to_le_bytes().as_ref()
+        ],
+        bump = session.bump,
+        constraint = session.client == client.key() @ InsuranceError::NotSessionParty
+    )]
+    pub session: Account<'info, Session>,
+
+    pub client: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmSessionService<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"session",
+            session.client.as_ref(),
+            session.provider.as_ref(),
+            session.nonce.to_le_bytes().as_ref()
+        ],
+        bump = session.bump,
+        constraint = session.provider == provider.key() @ InsuranceError::NotSessionParty
+    )]
+    pub session: Account<'info, Session>,
+
+    pub provider: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, used to introspect the client's receipt signature
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSession<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, InsuranceConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"session",
+            session.client.as_ref(),
+            session.provider.as_ref(),
+            session.nonce.to_le_bytes().as_ref()
+        ],
+        bump = session.bump,
+        close = client
+    )]
+    pub session: Account<'info, Session>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", session.provider.as_ref(), session.mint.as_ref()],
+        bump = provider_bond.bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", session.mint.as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Provider, or the client once the session has expired
+    pub closer: Signer<'info>,
+
+    /// Receives the session's rent
+    #[account(mut, address = session.client @ InsuranceError::InvalidPayer)]
+    pub client: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = client_token_account.mint == session.mint @ InsuranceError::MintMismatch,
+        constraint = client_token_account.owner == session.client @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub client_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = platform_treasury_token_account.mint == session.mint @ InsuranceError::MintMismatch,
+        constraint = platform_treasury_token_account.owner == config.platform_treasury @ InsuranceError::InvalidTreasuryAccount
+    )]
+    pub platform_treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = session.mint @ InsuranceError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
 pub struct WithdrawBond<'info> {
     #[account(
         mut,
-        seeds = [b"provider_bond", provider.key().as_ref()],
+        seeds = [b"provider_bond", provider.key().as_ref(), provider_bond.mint.as_ref()],
         bump = provider_bond.bump,
         constraint = provider_bond.provider == provider.key()
     )]
@@ -603,18 +3001,31 @@ pub struct WithdrawBond<'info> {
 
     #[account(
         mut,
-        seeds = [b"vault"],
+        seeds = [b"vault", provider_bond.mint.as_ref()],
         bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"escrow_ledger", provider_bond.mint.as_ref()],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
 
     #[account(mut)]
     pub provider: Signer<'info>,
 
-    #[account(mut)]
-    pub provider_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = provider_token_account.mint == provider_bond.mint @ InsuranceError::MintMismatch,
+        constraint = provider_token_account.owner == provider.key() @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub provider_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = provider_bond.mint @ InsuranceError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
@@ -624,20 +3035,242 @@ pub struct LiquidateProvider<'info> {
 
     #[account(
         mut,
-        seeds = [b"provider_bond", provider_bond.provider.as_ref()],
+        seeds = [b"provider_bond", provider_bond.provider.as_ref(), provider_bond.mint.as_ref()],
+        bump = provider_bond.bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", provider_bond.mint.as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"escrow_ledger", provider_bond.mint.as_ref()],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
+
+    /// Anyone; receives the liquidation bonus
+    pub liquidator: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = liquidator_token_account.mint == provider_bond.mint @ InsuranceError::MintMismatch,
+        constraint = liquidator_token_account.owner == liquidator.key() @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub liquidator_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = platform_treasury_token_account.mint == provider_bond.mint @ InsuranceError::MintMismatch,
+        constraint = platform_treasury_token_account.owner == config.platform_treasury @ InsuranceError::InvalidTreasuryAccount
+    )]
+    pub platform_treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = provider_bond.mint @ InsuranceError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ReinstateProvider<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, InsuranceConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", provider.key().as_ref(), mint.key().as_ref()],
+        bump = provider_bond.bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    pub provider: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = provider_token_account.mint == mint.key() @ InsuranceError::MintMismatch,
+        constraint = provider_token_account.owner == provider.key() @ InsuranceError::InvalidTokenAccountOwner
+    )]
+    pub provider_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", mint.key().as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Approves the reinstatement, required when the config asks for approval
+    #[account(address = config.authority @ InsuranceError::Unauthorized)]
+    pub authority: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct SweepLiquidatedBond<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, InsuranceConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", provider_bond.provider.as_ref(), provider_bond.mint.as_ref()],
         bump = provider_bond.bump
     )]
     pub provider_bond: Account<'info, ProviderBond>,
 
     #[account(
         mut,
-        seeds = [b"vault"],
+        seeds = [b"vault", provider_bond.mint.as_ref()],
         bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"escrow_ledger", provider_bond.mint.as_ref()],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
+
+    #[account(
+        mut,
+        constraint = platform_treasury_token_account.mint == provider_bond.mint @ InsuranceError::MintMismatch,
+        constraint = platform_treasury_token_account.owner == config.platform_treasury @ InsuranceError::InvalidTreasuryAccount
+    )]
+    pub platform_treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = provider_bond.mint @ InsuranceError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateLegacyConfig<'info> {
+    /// CHECK: First-release config; the handler checks its layout and rewrites it
+    #[account(mut, seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(request_commitment: [u8; 32])]
+pub struct MigrateLegacyClaim<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority @ InsuranceError::Unauthorized
+    )]
+    pub config: Account<'info, InsuranceConfig>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK: First-release claim; the handler checks its layout and closes it
+    #[account(mut, seeds = [b"claim", request_commitment.as_ref()], bump)]
+    pub legacy_claim: UncheckedAccount<'info>,
+
+    /// CHECK: First-release bond of the claim's provider; checked by the handler
+    #[account(mut)]
+    pub legacy_provider_bond: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub legacy_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = legacy_vault.mint @ InsuranceError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Claim's client, receives the claim's rent; checked by the handler
+    #[account(mut)]
+    pub client: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = client_token_account.mint == mint.key() @ InsuranceError::MintMismatch
+    )]
+    pub client_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = platform_treasury_token_account.mint == mint.key() @ InsuranceError::MintMismatch,
+        constraint = platform_treasury_token_account.owner == config.platform_treasury @ InsuranceError::InvalidTreasuryAccount
+    )]
+    pub platform_treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateLegacyBond<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority @ InsuranceError::Unauthorized
+    )]
+    pub config: Account<'info, InsuranceConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
 
+    /// CHECK: Owner of the legacy bond, receives its rent
     #[account(mut)]
-    pub platform_treasury_token_account: Account<'info, TokenAccount>,
+    pub provider: UncheckedAccount<'info>,
+
+    /// CHECK: First-release bond; the handler checks its layout and closes it
+    #[account(mut, seeds = [b"provider_bond", provider.key().as_ref()], bump)]
+    pub legacy_provider_bond: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub legacy_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = legacy_vault.mint @ InsuranceError::MintMismatch)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = ProviderBond::LEN,
+        seeds = [b"provider_bond", provider.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub provider_bond: Account<'info, ProviderBond>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        token::mint = mint,
+        token::authority = vault,
+        token::token_program = token_program,
+        seeds = [b"vault", mint.key().as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = EscrowLedger::LEN,
+        seeds = [b"escrow_ledger", mint.key().as_ref()],
+        bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
+
+    #[account(
+        mut,
+        constraint = platform_treasury_token_account.mint == mint.key() @ InsuranceError::MintMismatch,
+        constraint = platform_treasury_token_account.owner == config.platform_treasury @ InsuranceError::InvalidTreasuryAccount
+    )]
+    pub platform_treasury_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
 }
@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::errors::InsuranceError;
+use crate::state::{ClaimStatus, InsuranceClaim, InsuranceConfig, ProviderBond};
+
+/// Seed of the single vault every mint shared before vaults were keyed by mint
+pub const LEGACY_VAULT_SEED: &[u8] = b"vault";
+
+/// `InsuranceConfig` as laid out by the first release
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyInsuranceConfig {
+    pub platform_treasury: Pubkey,
+    pub platform_penalty_rate: u16,
+    pub default_timeout: u64,
+    pub liquidation_grace_period: u64,
+    pub authority: Pubkey,
+    pub bump: u8,
+}
+
+impl LegacyInsuranceConfig {
+    pub const LEN: usize = 8 + 32 + 2 + 8 + 8 + 32 + 1;
+
+    pub fn load(info: &AccountInfo) -> Result<Self> {
+        load_legacy(info, InsuranceConfig::DISCRIMINATOR, Self::LEN)
+    }
+}
+
+/// `ProviderBond` as laid out by the first release, seeded by provider only
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyProviderBond {
+    pub provider: Pubkey,
+    pub total_bond: u64,
+    pub locked_bond: u64,
+    pub min_bond: u64,
+    pub is_liquidated: bool,
+    pub undercollateralized_since: i64,
+    pub bump: u8,
+}
+
+impl LegacyProviderBond {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 1 + 8 + 1;
+
+    pub fn load(info: &AccountInfo) -> Result<Self> {
+        load_legacy(info, ProviderBond::DISCRIMINATOR, Self::LEN)
+    }
+
+    /// Address of the legacy bond PDA `["provider_bond", provider]`
+    pub fn address(provider: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"provider_bond", provider.as_ref()], &crate::ID).0
+    }
+
+    /// Write the bond back in its legacy layout
+    pub fn store(&self, info: &AccountInfo) -> Result<()> {
+        let mut data = info.try_borrow_mut_data()?;
+        self.serialize(&mut &mut data[8..])?;
+        Ok(())
+    }
+}
+
+/// `InsuranceClaim` as laid out by the first release, seeded by commitment only
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyInsuranceClaim {
+    pub request_commitment: [u8; 32],
+    pub client: Pubkey,
+    pub provider: Pubkey,
+    pub payment_amount: u64,
+    pub locked_amount: u64,
+    pub deadline: i64,
+    pub status: ClaimStatus,
+    pub bump: u8,
+}
+
+impl LegacyInsuranceClaim {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 1 + 1;
+
+    pub fn load(info: &AccountInfo) -> Result<Self> {
+        load_legacy(info, InsuranceClaim::DISCRIMINATOR, Self::LEN)
+    }
+}
+
+/// Deserialize an account this program wrote in a legacy layout
+///
+/// The discriminators did not change, so the exact legacy length is what
+/// tells an old account apart from a current one.
+fn load_legacy<T: AnchorDeserialize>(info: &AccountInfo, discriminator: &[u8], len: usize) -> Result<T> {
+    require_keys_eq!(*info.owner, crate::ID, InsuranceError::InvalidLegacyAccount);
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() == len && data.starts_with(discriminator),
+        InsuranceError::InvalidLegacyAccount
+    );
+    T::deserialize(&mut &data[discriminator.len()..])
+        .map_err(|_| error!(InsuranceError::InvalidLegacyAccount))
+}
+
+/// Grow a legacy account to `len` bytes, topping up its rent from `payer`
+pub fn grow<'info>(
+    info: &AccountInfo<'info>,
+    len: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let shortfall = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(info.lamports());
+    if shortfall > 0 {
+        let cpi_accounts = Transfer {
+            from: payer.clone(),
+            to: info.clone(),
+        };
+        system_program::transfer(CpiContext::new(system_program.clone(), cpi_accounts), shortfall)?;
+    }
+    info.resize(len)?;
+    Ok(())
+}
+
+/// Close a legacy account and send its rent to `destination`
+pub fn close<'info>(info: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = destination.lamports()
+        .checked_add(info.lamports())
+        .ok_or(InsuranceError::ArithmeticOverflow)?;
+    **destination.try_borrow_mut_lamports()? = lamports;
+    **info.try_borrow_mut_lamports()? = 0;
+
+    info.assign(&system_program::ID);
+    info.resize(0)?;
+    Ok(())
+}
+
+/// Transfer tokens out of the legacy vault, signed by its `["vault"]` PDA
+pub fn transfer_from_legacy_vault<'info>(
+    legacy_vault: &InterfaceAccount<'info, TokenAccount>,
+    legacy_vault_bump: u8,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[LEGACY_VAULT_SEED, &[legacy_vault_bump]];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = TransferChecked {
+        from: legacy_vault.to_account_info(),
+        mint: mint.to_account_info(),
+        to,
+        authority: legacy_vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)
+}
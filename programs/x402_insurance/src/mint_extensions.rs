@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        transfer_hook::TransferHook, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    state::Mint as MintState,
+};
+use anchor_spl::token_interface::Mint;
+
+use crate::errors::InsuranceError;
+
+/// Reject Token-2022 mints whose extensions break vault accounting
+///
+/// Transfer fees are handled by measuring what actually arrives, and
+/// interest-bearing mints only change the UI amount, so both are allowed.
+/// Transfer hooks need extra accounts on every transfer and non-transferable
+/// mints cannot move at all, so neither can back a bond. A permanent delegate
+/// could move tokens out of the shared vault, and pausable or default-frozen
+/// mints could lock it, so those are rejected as well.
+pub fn check_supported_mint(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner == anchor_spl::token::ID {
+        return Ok(());
+    }
+
+    let data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&data)?;
+
+    for extension in mint_state.get_extension_types()? {
+        match extension {
+            ExtensionType::NonTransferable
+            | ExtensionType::PermanentDelegate
+            | ExtensionType::Pausable
+            | ExtensionType::DefaultAccountState => {
+                return err!(InsuranceError::UnsupportedMintExtension);
+            }
+            ExtensionType::TransferHook => {
+                let hook = mint_state.get_extension::<TransferHook>()?;
+                require!(
+                    Option::<Pubkey>::from(hook.program_id).is_none(),
+                    InsuranceError::UnsupportedMintExtension
+                );
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
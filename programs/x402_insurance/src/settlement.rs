@@ -0,0 +1,421 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::errors::InsuranceError;
+use crate::events::{emit_collateral_transition, record_slash, DisputeResolved, InsuranceClaimed};
+use crate::state::{
+    apply_bps, ClaimStatus, DisputeRuling, EscrowLedger, InsuranceClaim, InsuranceConfig, ProviderBond,
+    SlashReason, BPS_DENOMINATOR,
+};
+
+/// Accounts needed to pay a claim out of a mint's vault
+pub struct ClaimPayout<'a, 'info> {
+    pub vault: &'a InterfaceAccount<'info, TokenAccount>,
+    pub vault_bump: u8,
+    pub client_token_account: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub platform_treasury_token_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    /// Diverts part of the penalty to whoever settled an expired claim
+    pub crank_tip: Option<CrankTip<'a, 'info>>,
+}
+
+/// Cranker's share of the penalty when settling someone else's claim
+pub struct CrankTip<'a, 'info> {
+    pub token_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub tip_bps: u16,
+}
+
+/// What `ClaimPayout::pay` delivered
+pub struct PaymentReceipt {
+    /// Refund that reached the client, net of Token-2022 transfer fees
+    pub client_received: u64,
+    /// Part of the penalty paid to the cranker instead of the treasury
+    pub tip_amount: u64,
+}
+
+impl ClaimPayout<'_, '_> {
+    /// Send `refund_amount` to the client and `penalty_amount` to the treasury
+    /// (less any crank tip)
+    ///
+    /// The client's share is reported net of the fee when the mint charges
+    /// Token-2022 transfer fees.
+    pub fn pay(&mut self, refund_amount: u64, penalty_amount: u64) -> Result<PaymentReceipt> {
+        let client_balance_before = self.client_token_account.amount;
+        transfer_from_vault(
+            self.vault,
+            self.vault_bump,
+            self.mint,
+            self.token_program,
+            self.client_token_account.to_account_info(),
+            refund_amount,
+        )?;
+
+        self.client_token_account.reload()?;
+        let client_received = self.client_token_account.amount
+            .checked_sub(client_balance_before)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+
+        let tip_amount = match &self.crank_tip {
+            Some(crank_tip) => apply_bps(penalty_amount, crank_tip.tip_bps)?,
+            None => 0,
+        };
+        if let (Some(crank_tip), true) = (&self.crank_tip, tip_amount > 0) {
+            transfer_from_vault(
+                self.vault,
+                self.vault_bump,
+                self.mint,
+                self.token_program,
+                crank_tip.token_account.to_account_info(),
+                tip_amount,
+            )?;
+        }
+
+        let treasury_amount = penalty_amount
+            .checked_sub(tip_amount)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        if treasury_amount > 0 {
+            transfer_from_vault(
+                self.vault,
+                self.vault_bump,
+                self.mint,
+                self.token_program,
+                self.platform_treasury_token_account.to_account_info(),
+                treasury_amount,
+            )?;
+        }
+
+        Ok(PaymentReceipt { client_received, tip_amount })
+    }
+}
+
+/// Accounts needed to move a claim's escrowed payment out of the vault
+pub struct EscrowRelease<'a, 'info> {
+    pub vault: &'a InterfaceAccount<'info, TokenAccount>,
+    pub vault_bump: u8,
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    pub escrow_ledger: &'a mut EscrowLedger,
+}
+
+impl<'info> EscrowRelease<'_, 'info> {
+    /// Send `amount` of the claim's escrowed payment to `to`
+    pub fn release(
+        &mut self,
+        claim: &mut InsuranceClaim,
+        to: &InterfaceAccount<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        claim.escrow_amount = claim.escrow_amount
+            .checked_sub(amount)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        self.escrow_ledger.release(amount)?;
+        transfer_from_vault(
+            self.vault,
+            self.vault_bump,
+            self.mint,
+            self.token_program,
+            to.to_account_info(),
+            amount,
+        )
+    }
+
+    /// Return `payout_bps` of what is still escrowed to the client
+    pub fn refund_share(
+        &mut self,
+        claim: &mut InsuranceClaim,
+        client_token_account: &InterfaceAccount<'info, TokenAccount>,
+        payout_bps: u16,
+    ) -> Result<u64> {
+        let amount = apply_bps(claim.escrow_amount, payout_bps)?;
+        self.release(claim, client_token_account, amount)?;
+        Ok(amount)
+    }
+}
+
+/// Release a claim's remaining escrow to the provider through accounts that
+/// are only required when something is escrowed
+///
+/// Returns the amount released.
+pub fn release_escrow_to_provider<'info>(
+    claim: &mut InsuranceClaim,
+    vault: Option<&InterfaceAccount<'info, TokenAccount>>,
+    vault_bump: Option<u8>,
+    mint: Option<&InterfaceAccount<'info, Mint>>,
+    token_program: Option<&Interface<'info, TokenInterface>>,
+    escrow_ledger: Option<&mut EscrowLedger>,
+    provider_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+) -> Result<u64> {
+    let amount = claim.escrow_amount;
+    if amount == 0 {
+        return Ok(0);
+    }
+    let (
+        Some(vault),
+        Some(vault_bump),
+        Some(mint),
+        Some(token_program),
+        Some(escrow_ledger),
+        Some(provider_token_account),
+    ) = (vault, vault_bump, mint, token_program, escrow_ledger, provider_token_account)
+    else {
+        return err!(InsuranceError::MissingEscrowAccounts);
+    };
+    EscrowRelease { vault, vault_bump, mint, token_program, escrow_ledger }
+        .release(claim, provider_token_account, amount)?;
+    Ok(amount)
+}
+
+/// Take up to `amount` of a provider's available bond and send it to `to`
+///
+/// Locked bond is never touched. Returns the amount actually seized.
+pub fn seize_available_bond<'info>(
+    provider_bond: &mut ProviderBond,
+    amount: u64,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    vault_bump: u8,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+) -> Result<u64> {
+    let seized_amount = amount.min(provider_bond.available_bond()?);
+    if seized_amount == 0 {
+        return Ok(0);
+    }
+    provider_bond.total_bond = provider_bond.total_bond
+        .checked_sub(seized_amount)
+        .ok_or(InsuranceError::ArithmeticOverflow)?;
+    transfer_from_vault(vault, vault_bump, mint, token_program, to.to_account_info(), seized_amount)?;
+    Ok(seized_amount)
+}
+
+/// Transfer tokens out of a mint's vault, signed by the vault PDA
+pub(crate) fn transfer_from_vault<'info>(
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    vault_bump: u8,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let mint_key = mint.key();
+    let seeds = &[
+        b"vault".as_ref(),
+        mint_key.as_ref(),
+        &[vault_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = TransferChecked {
+        from: vault.to_account_info(),
+        mint: mint.to_account_info(),
+        to,
+        authority: vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)
+}
+
+/// What one settlement step paid out of the bond
+pub struct PayoutReceipt {
+    pub refund_amount: u64,
+    pub penalty_amount: u64,
+    pub client_received: u64,
+    pub tip_amount: u64,
+}
+
+/// Raise a claim's payout to `payout_bps` of its timeout payout
+///
+/// Only the difference to what was already paid leaves the vault, and it is
+/// deducted from the provider's locked bond.
+pub fn raise_payout(
+    claim: &mut InsuranceClaim,
+    provider_bond: &mut ProviderBond,
+    payout: &mut ClaimPayout,
+    payout_bps: u16,
+) -> Result<PayoutReceipt> {
+    let (refund_due, penalty_due) = claim.payout_for(payout_bps)?;
+    let (refund_paid, penalty_paid) = claim.payout_for(claim.payout_bps)?;
+    let refund_amount = refund_due.saturating_sub(refund_paid);
+    let penalty_amount = penalty_due.saturating_sub(penalty_paid);
+
+    let bond_deduction = refund_amount
+        .checked_add(penalty_amount)
+        .ok_or(InsuranceError::ArithmeticOverflow)?;
+    provider_bond.total_bond = provider_bond.total_bond
+        .checked_sub(bond_deduction)
+        .ok_or(InsuranceError::InsufficientBond)?;
+    provider_bond.locked_bond = provider_bond.locked_bond
+        .checked_sub(bond_deduction)
+        .ok_or(InsuranceError::ArithmeticOverflow)?;
+
+    let paid = if bond_deduction > 0 {
+        payout.pay(refund_amount, penalty_amount)?
+    } else {
+        PaymentReceipt { client_received: 0, tip_amount: 0 }
+    };
+    claim.payout_bps = claim.payout_bps.max(payout_bps);
+
+    Ok(PayoutReceipt {
+        refund_amount,
+        penalty_amount,
+        client_received: paid.client_received,
+        tip_amount: paid.tip_amount,
+    })
+}
+
+/// Unlock whatever bond a claim still holds and drop it from the active count
+///
+/// Returns the amount unlocked.
+pub fn unlock_claim(claim: &InsuranceClaim, provider_bond: &mut ProviderBond) -> Result<u64> {
+    let unlocked_amount = claim.locked_remaining()?;
+    provider_bond.locked_bond = provider_bond.locked_bond
+        .checked_sub(unlocked_amount)
+        .ok_or(InsuranceError::ArithmeticOverflow)?;
+    provider_bond.active_claims = provider_bond.active_claims
+        .checked_sub(1)
+        .ok_or(InsuranceError::ArithmeticOverflow)?;
+    Ok(unlocked_amount)
+}
+
+/// Pay out a pending claim whose deadline passed and mark it claimed
+///
+/// Amounts were snapshotted at purchase, so config changes never affect the
+/// claim: the client is refunded the payment (net of Token-2022 transfer fees)
+/// and the platform gets the penalty, both out of the provider's locked bond,
+/// and an escrowed payment goes back to the client in full. Shared by
+/// `claim_insurance` and `settle_expired_claim`, which adds a crank tip.
+pub fn settle_timed_out_claim(
+    claim: &mut Account<InsuranceClaim>,
+    provider_bond: &mut ProviderBond,
+    mut payout: ClaimPayout,
+    escrow_ledger: &mut EscrowLedger,
+    now: i64,
+) -> Result<PayoutReceipt> {
+    let receipt = raise_payout(claim, provider_bond, &mut payout, BPS_DENOMINATOR as u16)?;
+    unlock_claim(claim, provider_bond)?;
+
+    let escrow_refunded = claim.escrow_amount;
+    EscrowRelease {
+        vault: payout.vault,
+        vault_bump: payout.vault_bump,
+        mint: payout.mint,
+        token_program: payout.token_program,
+        escrow_ledger,
+    }
+    .release(claim, payout.client_token_account, escrow_refunded)?;
+
+    record_slash(provider_bond, SlashReason::Timeout, receipt.penalty_amount, now)?;
+    let transition = provider_bond.update_collateral_status(now)?;
+    claim.status = ClaimStatus::Claimed;
+
+    emit!(InsuranceClaimed {
+        claim: claim.key(),
+        request_commitment: claim.request_commitment,
+        client: claim.client,
+        provider: claim.provider,
+        mint: claim.mint,
+        refund_amount: receipt.refund_amount,
+        client_received: receipt.client_received,
+        penalty_amount: receipt.penalty_amount,
+        total_bond: provider_bond.total_bond,
+        escrow_refunded,
+        timestamp: now,
+    });
+    emit_collateral_transition(provider_bond, transition, now)?;
+    Ok(receipt)
+}
+
+/// Apply a ruling to a disputed claim
+///
+/// Ruling for the provider unlocks the bond; ruling for the client pays the
+/// snapshotted refund and penalty out of the bond as on a timeout (less any
+/// partial payout made at confirmation). Rulings against the provider also
+/// take the reason's extra penalty from available bond. Whatever is still
+/// escrowed follows the ruling. Shared by `resolve_dispute` and
+/// `expire_dispute`, which applies `DisputeRuling::DEFAULT`.
+pub fn settle_dispute<'info>(
+    claim: &mut Account<InsuranceClaim>,
+    provider_bond: &mut ProviderBond,
+    mut payout: ClaimPayout<'_, 'info>,
+    escrow_ledger: &mut EscrowLedger,
+    provider_token_account: &InterfaceAccount<'info, TokenAccount>,
+    config: &InsuranceConfig,
+    ruling: DisputeRuling,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+
+    // The client is owed the full timeout payout, less any partial payout already made
+    let payout_bps = match ruling {
+        DisputeRuling::Provider => claim.payout_bps,
+        DisputeRuling::Client | DisputeRuling::InvalidProof => BPS_DENOMINATOR as u16,
+    };
+    let receipt = raise_payout(claim, provider_bond, &mut payout, payout_bps)?;
+    let unlocked_amount = unlock_claim(claim, provider_bond)?;
+
+    // Losing a dispute costs the provider more than a plain timeout
+    let slash_penalty = match ruling.slash_reason() {
+        Some(reason) => {
+            let extra = apply_bps(claim.payment_amount, config.slash_rate_bps(reason))?;
+            let slash_penalty = seize_available_bond(
+                provider_bond,
+                extra,
+                payout.vault,
+                payout.vault_bump,
+                payout.mint,
+                payout.token_program,
+                payout.platform_treasury_token_account,
+            )?;
+            let slashed = receipt.penalty_amount
+                .checked_add(slash_penalty)
+                .ok_or(InsuranceError::ArithmeticOverflow)?;
+            record_slash(provider_bond, reason, slashed, now)?;
+            slash_penalty
+        }
+        None => 0,
+    };
+    let transition = provider_bond.update_collateral_status(now)?;
+
+    let escrowed = claim.escrow_amount;
+    let (payee, escrow_refunded, escrow_released) = match ruling {
+        DisputeRuling::Provider => (provider_token_account, 0, escrowed),
+        DisputeRuling::Client | DisputeRuling::InvalidProof => {
+            (&*payout.client_token_account, escrowed, 0)
+        }
+    };
+    EscrowRelease {
+        vault: payout.vault,
+        vault_bump: payout.vault_bump,
+        mint: payout.mint,
+        token_program: payout.token_program,
+        escrow_ledger,
+    }
+    .release(claim, payee, escrowed)?;
+
+    let defaulted = now >= claim.dispute_deadline;
+    claim.status = match ruling {
+        DisputeRuling::Provider => ClaimStatus::Confirmed,
+        DisputeRuling::Client | DisputeRuling::InvalidProof => ClaimStatus::Claimed,
+    };
+    claim.dispute_deadline = 0;
+
+    emit!(DisputeResolved {
+        claim: claim.key(),
+        request_commitment: claim.request_commitment,
+        arbiter: claim.arbiter,
+        ruling,
+        defaulted,
+        refund_amount: receipt.refund_amount,
+        client_received: receipt.client_received,
+        penalty_amount: receipt.penalty_amount,
+        slash_penalty,
+        unlocked_amount,
+        escrow_refunded,
+        escrow_released,
+        timestamp: now,
+    });
+    emit_collateral_transition(provider_bond, transition, now)
+}
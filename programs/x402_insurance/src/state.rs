@@ -1,4 +1,19 @@
 use anchor_lang::prelude::*;
+use solana_sha256_hasher::hashv;
+
+use crate::errors::InsuranceError;
+
+/// Basis point denominator (10000 = 100%)
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Apply a basis point rate to an amount, rounding down
+pub fn apply_bps(amount: u64, bps: u16) -> Result<u64> {
+    let result = (amount as u128)
+        .checked_mul(bps as u128)
+        .and_then(|v| v.checked_div(BPS_DENOMINATOR as u128))
+        .ok_or(InsuranceError::ArithmeticOverflow)?;
+    u64::try_from(result).map_err(|_| error!(InsuranceError::ArithmeticOverflow))
+}
 
 /// Global insurance configuration
 #[account]
@@ -7,12 +22,50 @@ pub struct InsuranceConfig {
     pub platform_treasury: Pubkey,
     /// Penalty rate (in basis points, e.g., 200 = 2%)
     pub platform_penalty_rate: u16,
+    /// Bond locked per insured payment (in basis points, e.g., 10200 = 1.02x)
+    pub coverage_multiplier_bps: u16,
     /// Default timeout in seconds (e.g., 300 = 5 minutes)
     pub default_timeout: u64,
     /// Grace period for undercollateralized providers (in seconds)
     pub liquidation_grace_period: u64,
+    /// Minimum bond assigned to providers on their first deposit
+    pub default_min_bond: u64,
     /// Admin authority
     pub authority: Pubkey,
+    /// Authority proposed via `propose_authority` (default if none pending)
+    pub pending_authority: Pubkey,
+    /// Emergency stop: blocks new purchases and deposits while settlement continues
+    pub paused: bool,
+    /// When the protocol was last unpaused (0 if never); liquidation grace
+    /// periods restart from here, since providers cannot top up while paused
+    pub unpaused_at: i64,
+    /// How long after confirmation a client may dispute, and then the arbiter
+    /// has to rule (in seconds, 0 disables)
+    pub dispute_window: u64,
+    /// Resolves disputes unless the provider's policy names its own arbiter
+    pub arbiter: Pubkey,
+    /// Extra time after a claim's deadline before anyone may settle it (in seconds)
+    pub crank_delay: u64,
+    /// Share of the penalty paid to whoever settles an expired claim (in basis points)
+    pub crank_tip_bps: u16,
+    /// Share of seized bond paid to the liquidator once the grace period ends (in basis points)
+    pub liquidation_bonus_bps: u16,
+    /// Share the liquidation bonus rises to over `liquidation_bonus_ramp` (in basis points)
+    pub max_liquidation_bonus_bps: u16,
+    /// Seconds for the bonus to rise from its start to its maximum (0 keeps it flat)
+    pub liquidation_bonus_ramp: u64,
+    /// Bond a liquidated provider must bring back, as a multiple of `min_bond` (in basis points)
+    pub reinstatement_bond_bps: u16,
+    /// Seconds after liquidation before a provider may be reinstated
+    pub reinstatement_cooldown: u64,
+    /// Whether reinstatement needs the authority's signature
+    pub reinstatement_requires_approval: bool,
+    /// Share of `min_bond` seized per partial liquidation (in basis points, 0 seizes everything available)
+    pub liquidation_penalty_bps: u16,
+    /// Extra penalty when a dispute is lost (in basis points of the claim's payment)
+    pub dispute_loss_penalty_bps: u16,
+    /// Extra penalty when the arbiter finds the delivery proof invalid (in basis points of the claim's payment)
+    pub invalid_signature_penalty_bps: u16,
     /// PDA bump
     pub bump: u8,
 }
@@ -21,27 +74,276 @@ impl InsuranceConfig {
     pub const LEN: usize = 8 + // discriminator
         32 + // platform_treasury
         2 +  // platform_penalty_rate
+        2 +  // coverage_multiplier_bps
         8 +  // default_timeout
         8 +  // liquidation_grace_period
+        8 +  // default_min_bond
         32 + // authority
+        32 + // pending_authority
+        1 +  // paused
+        8 +  // unpaused_at
+        8 +  // dispute_window
+        32 + // arbiter
+        8 +  // crank_delay
+        2 +  // crank_tip_bps
+        2 +  // liquidation_bonus_bps
+        2 +  // max_liquidation_bonus_bps
+        8 +  // liquidation_bonus_ramp
+        2 +  // reinstatement_bond_bps
+        8 +  // reinstatement_cooldown
+        1 +  // reinstatement_requires_approval
+        2 +  // liquidation_penalty_bps
+        2 +  // dispute_loss_penalty_bps
+        2 +  // invalid_signature_penalty_bps
         1;   // bump
+
+    /// Maximum penalty rate (100%)
+    pub const MAX_PENALTY_RATE: u16 = 10_000;
+    /// Shortest allowed claim timeout (1 minute)
+    pub const MIN_TIMEOUT: u64 = 60;
+    /// Longest allowed claim timeout (7 days)
+    pub const MAX_TIMEOUT: u64 = 7 * 24 * 60 * 60;
+    /// Longest allowed liquidation grace period (30 days)
+    pub const MAX_LIQUIDATION_GRACE_PERIOD: u64 = 30 * 24 * 60 * 60;
+    /// Longest allowed dispute window (7 days)
+    pub const MAX_DISPUTE_WINDOW: u64 = 7 * 24 * 60 * 60;
+    /// Longest allowed crank delay (7 days)
+    pub const MAX_CRANK_DELAY: u64 = 7 * 24 * 60 * 60;
+    /// Longest allowed liquidation bonus ramp (30 days)
+    pub const MAX_LIQUIDATION_BONUS_RAMP: u64 = 30 * 24 * 60 * 60;
+    /// Longest allowed reinstatement cool-down (90 days)
+    pub const MAX_REINSTATEMENT_COOLDOWN: u64 = 90 * 24 * 60 * 60;
+
+    /// Configuration with every optional feature switched off
+    pub fn new(
+        platform_treasury: Pubkey,
+        authority: Pubkey,
+        platform_penalty_rate: u16,
+        default_timeout: u64,
+        liquidation_grace_period: u64,
+        default_min_bond: u64,
+        bump: u8,
+    ) -> Self {
+        let mut config = Self {
+            platform_treasury,
+            platform_penalty_rate,
+            coverage_multiplier_bps: 0,
+            default_timeout,
+            liquidation_grace_period,
+            default_min_bond,
+            authority,
+            pending_authority: Pubkey::default(),
+            paused: false,
+            unpaused_at: 0,
+            dispute_window: 0,
+            arbiter: Pubkey::default(),
+            crank_delay: 0,
+            crank_tip_bps: 0,
+            liquidation_bonus_bps: 0,
+            max_liquidation_bonus_bps: 0,
+            liquidation_bonus_ramp: 0,
+            reinstatement_bond_bps: BPS_DENOMINATOR as u16,
+            reinstatement_cooldown: 0,
+            reinstatement_requires_approval: false,
+            liquidation_penalty_bps: 0,
+            dispute_loss_penalty_bps: 0,
+            invalid_signature_penalty_bps: 0,
+            bump,
+        };
+        config.coverage_multiplier_bps = config.min_coverage_multiplier_bps();
+        config
+    }
+
+    /// Check all parameters are within protocol bounds
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.platform_penalty_rate <= Self::MAX_PENALTY_RATE,
+            InsuranceError::InvalidPenaltyRate
+        );
+        self.validate_coverage_multiplier(self.coverage_multiplier_bps)?;
+        Self::validate_timeout(self.default_timeout)?;
+        require!(
+            self.liquidation_grace_period <= Self::MAX_LIQUIDATION_GRACE_PERIOD,
+            InsuranceError::InvalidGracePeriod
+        );
+        require!(
+            self.dispute_window <= Self::MAX_DISPUTE_WINDOW,
+            InsuranceError::InvalidDisputeWindow
+        );
+        require!(
+            self.crank_delay <= Self::MAX_CRANK_DELAY,
+            InsuranceError::InvalidCrankDelay
+        );
+        require!(
+            self.crank_tip_bps as u64 <= BPS_DENOMINATOR,
+            InsuranceError::InvalidCrankTip
+        );
+        require!(
+            self.liquidation_bonus_bps <= self.max_liquidation_bonus_bps
+                && self.max_liquidation_bonus_bps as u64 <= BPS_DENOMINATOR
+                && self.liquidation_bonus_ramp <= Self::MAX_LIQUIDATION_BONUS_RAMP,
+            InsuranceError::InvalidLiquidationBonus
+        );
+        require!(
+            self.reinstatement_bond_bps as u64 >= BPS_DENOMINATOR
+                && self.reinstatement_cooldown <= Self::MAX_REINSTATEMENT_COOLDOWN,
+            InsuranceError::InvalidReinstatementTerms
+        );
+        require!(
+            self.liquidation_penalty_bps as u64 <= BPS_DENOMINATOR,
+            InsuranceError::InvalidLiquidationPenalty
+        );
+        require!(
+            self.dispute_loss_penalty_bps <= Self::MAX_PENALTY_RATE
+                && self.invalid_signature_penalty_bps <= Self::MAX_PENALTY_RATE,
+            InsuranceError::InvalidPenaltyRate
+        );
+        Ok(())
+    }
+
+    /// Penalty rate for each slash reason (in basis points)
+    ///
+    /// Timeouts and the two dispute reasons are charged on the claim's payment;
+    /// liquidations on the provider's `min_bond`.
+    pub fn slash_rate_bps(&self, reason: SlashReason) -> u16 {
+        match reason {
+            SlashReason::Timeout => self.platform_penalty_rate,
+            SlashReason::DisputeLoss => self.dispute_loss_penalty_bps,
+            SlashReason::InvalidSignature => self.invalid_signature_penalty_bps,
+            SlashReason::Liquidation => self.liquidation_penalty_bps,
+        }
+    }
+
+    /// Liquidator's share of seized bond `elapsed` seconds after the grace period ended
+    ///
+    /// Rises linearly from `liquidation_bonus_bps` to `max_liquidation_bonus_bps`
+    /// over `liquidation_bonus_ramp`, so the bonus keeps growing until a
+    /// liquidator finds it worth taking.
+    pub fn liquidation_bonus_bps(&self, elapsed: i64) -> u16 {
+        if self.liquidation_bonus_ramp == 0 {
+            return self.liquidation_bonus_bps;
+        }
+        let elapsed = (elapsed.max(0) as u64).min(self.liquidation_bonus_ramp);
+        let rise = (self.max_liquidation_bonus_bps - self.liquidation_bonus_bps) as u64
+            * elapsed
+            / self.liquidation_bonus_ramp;
+        self.liquidation_bonus_bps + rise as u16
+    }
+
+    /// When an undercollateralized provider's grace period ends
+    ///
+    /// The grace clock restarts on unpause, so time spent paused (when the
+    /// provider could not deposit) never counts against it.
+    pub fn grace_period_end(&self, undercollateralized_since: i64) -> Result<i64> {
+        undercollateralized_since
+            .max(self.unpaused_at)
+            .checked_add(self.liquidation_grace_period as i64)
+            .ok_or(error!(InsuranceError::ArithmeticOverflow))
+    }
+
+    /// Smallest coverage multiplier that still covers a full refund plus the penalty
+    pub fn min_coverage_multiplier_bps(&self) -> u16 {
+        (BPS_DENOMINATOR as u16).saturating_add(self.platform_penalty_rate)
+    }
+
+    /// Check a coverage multiplier covers a full refund plus the penalty
+    pub fn validate_coverage_multiplier(&self, coverage_multiplier_bps: u16) -> Result<()> {
+        require!(
+            coverage_multiplier_bps >= self.min_coverage_multiplier_bps(),
+            InsuranceError::InvalidCoverageMultiplier
+        );
+        Ok(())
+    }
+
+    /// Check a claim timeout (in seconds) is within protocol bounds
+    pub fn validate_timeout(timeout_seconds: u64) -> Result<()> {
+        require!(
+            (Self::MIN_TIMEOUT..=Self::MAX_TIMEOUT).contains(&timeout_seconds),
+            InsuranceError::InvalidTimeout
+        );
+        Ok(())
+    }
+
+    /// Claim timeout in seconds for a purchase (0 minutes uses the default)
+    pub fn claim_timeout(&self, timeout_minutes: u64) -> Result<u64> {
+        let timeout_seconds = if timeout_minutes > 0 {
+            timeout_minutes
+                .checked_mul(60)
+                .ok_or(InsuranceError::InvalidTimeout)?
+        } else {
+            self.default_timeout
+        };
+        Self::validate_timeout(timeout_seconds)?;
+        Ok(timeout_seconds)
+    }
+}
+
+/// Optional parameter changes for `update_config` (None leaves a field unchanged)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateConfigParams {
+    pub platform_treasury: Option<Pubkey>,
+    pub platform_penalty_rate: Option<u16>,
+    pub coverage_multiplier_bps: Option<u16>,
+    pub default_timeout: Option<u64>,
+    pub liquidation_grace_period: Option<u64>,
+    pub default_min_bond: Option<u64>,
+    pub dispute_window: Option<u64>,
+    pub arbiter: Option<Pubkey>,
+    pub crank_delay: Option<u64>,
+    pub crank_tip_bps: Option<u16>,
+    pub liquidation_bonus_bps: Option<u16>,
+    pub max_liquidation_bonus_bps: Option<u16>,
+    pub liquidation_bonus_ramp: Option<u64>,
+    pub reinstatement_bond_bps: Option<u16>,
+    pub reinstatement_cooldown: Option<u64>,
+    pub reinstatement_requires_approval: Option<bool>,
+    pub liquidation_penalty_bps: Option<u16>,
+    pub dispute_loss_penalty_bps: Option<u16>,
+    pub invalid_signature_penalty_bps: Option<u16>,
+}
+
+/// Provider-signed terms for one request (see `ed25519::payment_quote_message`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PaymentQuote {
+    /// Exact payment amount the provider priced the request at
+    pub price: u64,
+    /// Longest claim timeout the provider accepts (in seconds)
+    pub max_timeout_seconds: u64,
+    /// Last slot at which the quote can be used
+    pub expiry_slot: u64,
 }
 
-/// Provider bond account (one per provider)
+/// Provider bond account (one per provider and mint)
 #[account]
 pub struct ProviderBond {
     /// Provider's wallet address
     pub provider: Pubkey,
+    /// Token mint the bond is denominated in
+    pub mint: Pubkey,
     /// Total bond deposited
     pub total_bond: u64,
     /// Currently locked bond (for active insurances)
     pub locked_bond: u64,
     /// Minimum bond required
     pub min_bond: u64,
-    /// Whether provider is liquidated
+    /// Whether provider is liquidated (winding down its pending claims)
     pub is_liquidated: bool,
     /// Timestamp when provider went below min_bond (0 if above min)
     pub undercollateralized_since: i64,
+    /// Coverage multiplier override in basis points (0 uses the config default)
+    pub coverage_multiplier_bps: u16,
+    /// Whether purchases must carry a provider-signed quote
+    pub requires_quote: bool,
+    /// Claims still pending against this bond
+    pub active_claims: u32,
+    /// Timestamp of liquidation (0 if not liquidated)
+    pub liquidated_at: i64,
+    /// Bond taken as penalties over the provider's lifetime (refunds excluded)
+    pub total_slashed: u64,
+    /// Number of slashes per `SlashReason`, indexed by the reason
+    pub slash_counts: [u32; SlashReason::COUNT],
+    /// Timestamp of the most recent slash (0 if never slashed)
+    pub last_slashed_at: i64,
     /// PDA bump
     pub bump: u8,
 }
@@ -49,12 +351,455 @@ pub struct ProviderBond {
 impl ProviderBond {
     pub const LEN: usize = 8 + // discriminator
         32 + // provider
+        32 + // mint
         8 +  // total_bond
         8 +  // locked_bond
         8 +  // min_bond
         1 +  // is_liquidated
         8 +  // undercollateralized_since
+        2 +  // coverage_multiplier_bps
+        1 +  // requires_quote
+        4 +  // active_claims
+        8 +  // liquidated_at
+        8 +  // total_slashed
+        4 * SlashReason::COUNT + // slash_counts
+        8 +  // last_slashed_at
+        1;   // bump
+
+    /// Initialize a bond on its first deposit
+    pub fn open(&mut self, provider: Pubkey, mint: Pubkey, min_bond: u64, bump: u8) {
+        self.provider = provider;
+        self.mint = mint;
+        self.bump = bump;
+        self.locked_bond = 0;
+        self.min_bond = min_bond;
+        self.is_liquidated = false;
+        self.undercollateralized_since = 0;
+        self.coverage_multiplier_bps = 0;
+        self.requires_quote = false;
+        self.active_claims = 0;
+        self.liquidated_at = 0;
+        self.total_slashed = 0;
+        self.slash_counts = [0; SlashReason::COUNT];
+        self.last_slashed_at = 0;
+    }
+
+    /// Coverage multiplier applied to new insurances for this provider
+    pub fn effective_coverage_multiplier_bps(&self, config: &InsuranceConfig) -> u16 {
+        if self.coverage_multiplier_bps > 0 {
+            self.coverage_multiplier_bps
+        } else {
+            config.coverage_multiplier_bps
+        }
+    }
+
+    /// Add a penalty to the slash ledger
+    ///
+    /// Returns false (and records nothing) for a zero amount.
+    pub fn record_slash(&mut self, reason: SlashReason, amount: u64, now: i64) -> Result<bool> {
+        if amount == 0 {
+            return Ok(false);
+        }
+        self.total_slashed = self.total_slashed
+            .checked_add(amount)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        let count = &mut self.slash_counts[reason as usize];
+        *count = count
+            .checked_add(1)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        self.last_slashed_at = now;
+        Ok(true)
+    }
+
+    /// Bond not locked by active insurances
+    pub fn available_bond(&self) -> Result<u64> {
+        self.total_bond
+            .checked_sub(self.locked_bond)
+            .ok_or(error!(InsuranceError::ArithmeticOverflow))
+    }
+
+    /// Start or clear the undercollateralization clock after the available
+    /// bond or `min_bond` changed
+    ///
+    /// A liquidated bond has no collateral clock; every change to it is a
+    /// wind-down step instead.
+    pub fn update_collateral_status(&mut self, now: i64) -> Result<CollateralTransition> {
+        if self.is_liquidated {
+            return Ok(CollateralTransition::WindingDown);
+        }
+
+        let available_bond = self.available_bond()?;
+
+        if available_bond < self.min_bond && self.undercollateralized_since == 0 {
+            self.undercollateralized_since = now;
+            return Ok(CollateralTransition::Undercollateralized);
+        }
+
+        if available_bond >= self.min_bond && self.undercollateralized_since > 0 {
+            self.undercollateralized_since = 0;
+            return Ok(CollateralTransition::Restored);
+        }
+
+        Ok(CollateralTransition::Unchanged)
+    }
+}
+
+/// Change in a provider's collateral health
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CollateralTransition {
+    /// No change
+    Unchanged,
+    /// Available bond fell below `min_bond`; grace period started
+    Undercollateralized,
+    /// Available bond is back at or above `min_bond`
+    Restored,
+    /// Provider is liquidated and its remaining bond changed
+    WindingDown,
+}
+
+/// Payments escrowed in a mint's vault (one per mint)
+///
+/// Escrowed payments share the vault with provider bonds, so instructions
+/// that move bond out of the vault check they leave this total behind.
+#[account]
+pub struct EscrowLedger {
+    /// Token mint of the vault
+    pub mint: Pubkey,
+    /// Payments currently escrowed across all claims
+    pub escrowed_amount: u64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl EscrowLedger {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // mint
+        8 +  // escrowed_amount
+        1;   // bump
+
+    /// Initialize the ledger on the first deposit for its mint
+    pub fn open(&mut self, mint: Pubkey, bump: u8) {
+        self.mint = mint;
+        self.escrowed_amount = 0;
+        self.bump = bump;
+    }
+
+    /// Record a payment escrowed into the vault
+    pub fn hold(&mut self, amount: u64) -> Result<()> {
+        self.escrowed_amount = self.escrowed_amount
+            .checked_add(amount)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Record an escrowed payment leaving the vault
+    pub fn release(&mut self, amount: u64) -> Result<()> {
+        self.escrowed_amount = self.escrowed_amount
+            .checked_sub(amount)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Require that moving `amount` of bond out of a vault holding
+    /// `vault_balance` still leaves every escrowed payment covered
+    pub fn check_bond_transfer(&self, vault_balance: u64, amount: u64) -> Result<()> {
+        let remaining = vault_balance
+            .checked_sub(amount)
+            .ok_or(InsuranceError::EscrowShortfall)?;
+        require!(remaining >= self.escrowed_amount, InsuranceError::EscrowShortfall);
+        Ok(())
+    }
+}
+
+/// Limits a provider places on what it insures (one per provider)
+///
+/// Zero means "no limit" for every numeric field, and an empty `allowed_mints`
+/// accepts any mint. Claim count and exposure limits apply per bond (mint).
+#[account]
+pub struct ProviderPolicy {
+    /// Provider's wallet address
+    pub provider: Pubkey,
+    /// Largest payment a single claim may insure
+    pub max_payment_amount: u64,
+    /// Shortest claim timeout accepted (in seconds)
+    pub min_timeout_seconds: u64,
+    /// Longest claim timeout accepted (in seconds)
+    pub max_timeout_seconds: u64,
+    /// Most claims pending against one bond at a time
+    pub max_concurrent_claims: u32,
+    /// Most bond that may be locked at a time
+    pub max_locked_exposure: u64,
+    /// Mints the provider insures (empty accepts any)
+    pub allowed_mints: Vec<Pubkey>,
+    /// Whether the provider has stopped accepting new insurance
+    pub paused: bool,
+    /// Arbiter for this provider's disputes (default uses the protocol arbiter)
+    pub arbiter: Pubkey,
+    /// Latency-tiered payouts owed on late delivery (empty pays nothing)
+    pub payout_schedule: Vec<PayoutTier>,
+    /// Hold payments in escrow until delivery is confirmed instead of paying up front
+    pub escrow_payments: bool,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl ProviderPolicy {
+    /// Most entries `allowed_mints` can hold
+    pub const MAX_ALLOWED_MINTS: usize = 8;
+
+    /// Load the provider's policy PDA, if the provider has created one
+    pub fn load(provider_policy: &AccountInfo) -> Result<Option<Self>> {
+        if provider_policy.owner != &crate::ID {
+            return Ok(None);
+        }
+        Ok(Some(Self::try_deserialize(&mut &provider_policy.try_borrow_data()?[..])?))
+    }
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // provider
+        8 +  // max_payment_amount
+        8 +  // min_timeout_seconds
+        8 +  // max_timeout_seconds
+        4 +  // max_concurrent_claims
+        8 +  // max_locked_exposure
+        4 + 32 * Self::MAX_ALLOWED_MINTS + // allowed_mints
+        1 +  // paused
+        32 + // arbiter
+        4 + PayoutTier::LEN * PayoutTier::MAX_TIERS + // payout_schedule
+        1 +  // escrow_payments
         1;   // bump
+
+    /// Check the policy itself is well formed
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.allowed_mints.len() <= Self::MAX_ALLOWED_MINTS,
+            InsuranceError::InvalidPolicy
+        );
+        require!(
+            self.max_timeout_seconds == 0 || self.min_timeout_seconds <= self.max_timeout_seconds,
+            InsuranceError::InvalidPolicy
+        );
+        // A provider cannot judge its own disputes
+        require!(self.arbiter != self.provider, InsuranceError::InvalidPolicy);
+        PayoutTier::validate_schedule(&self.payout_schedule)
+    }
+
+    /// Check a new insurance falls within what the provider is willing to cover
+    pub fn check_purchase(
+        &self,
+        provider_bond: &ProviderBond,
+        payment_amount: u64,
+        timeout_seconds: u64,
+        locked_amount: u64,
+    ) -> Result<()> {
+        require!(
+            timeout_seconds >= self.min_timeout_seconds
+                && (self.max_timeout_seconds == 0 || timeout_seconds <= self.max_timeout_seconds),
+            InsuranceError::TimeoutOutsidePolicy
+        );
+        self.check_coverage(provider_bond, payment_amount, locked_amount)
+    }
+
+    /// Check a new session falls within what the provider is willing to cover
+    ///
+    /// Timeout limits do not apply; `price_per_request` is held to the
+    /// per-payment limit.
+    pub fn check_session(
+        &self,
+        provider_bond: &ProviderBond,
+        price_per_request: u64,
+        locked_amount: u64,
+    ) -> Result<()> {
+        self.check_coverage(provider_bond, price_per_request, locked_amount)
+    }
+
+    fn check_coverage(
+        &self,
+        provider_bond: &ProviderBond,
+        payment_amount: u64,
+        locked_amount: u64,
+    ) -> Result<()> {
+        require!(!self.paused, InsuranceError::ProviderPaused);
+        require!(
+            self.allowed_mints.is_empty() || self.allowed_mints.contains(&provider_bond.mint),
+            InsuranceError::MintNotAllowed
+        );
+        require!(
+            self.max_payment_amount == 0 || payment_amount <= self.max_payment_amount,
+            InsuranceError::PaymentExceedsPolicy
+        );
+        require!(
+            self.max_concurrent_claims == 0
+                || provider_bond.active_claims < self.max_concurrent_claims,
+            InsuranceError::TooManyActiveClaims
+        );
+
+        let exposure = provider_bond.locked_bond
+            .checked_add(locked_amount)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        require!(
+            self.max_locked_exposure == 0 || exposure <= self.max_locked_exposure,
+            InsuranceError::ExposureLimitExceeded
+        );
+        Ok(())
+    }
+}
+
+/// New terms for `set_provider_policy` (replaces the whole policy)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProviderPolicyParams {
+    pub max_payment_amount: u64,
+    pub min_timeout_seconds: u64,
+    pub max_timeout_seconds: u64,
+    pub max_concurrent_claims: u32,
+    pub max_locked_exposure: u64,
+    pub allowed_mints: Vec<Pubkey>,
+    pub paused: bool,
+    pub arbiter: Pubkey,
+    pub payout_schedule: Vec<PayoutTier>,
+    pub escrow_payments: bool,
+}
+
+/// What a new claim locks and pays, snapshotted at purchase
+pub struct ClaimTerms {
+    /// Bond locked (payment_amount * coverage multiplier, e.g. 1.02x, less the
+    /// payment itself when it is escrowed)
+    pub locked_amount: u64,
+    /// Refunded to the client from the bond on a timeout claim: the payment,
+    /// or nothing when it is escrowed and refunds itself
+    pub refund_amount: u64,
+    /// Paid to the platform on a timeout claim
+    pub penalty_amount: u64,
+    /// Resolves disputes: the provider's choice, else the protocol's
+    pub arbiter: Pubkey,
+    /// Dispute window (0 when there is no arbiter)
+    pub dispute_window: u64,
+    /// Provider's latency-tiered payout schedule
+    pub payout_schedule: Vec<PayoutTier>,
+    /// Whether the payment is held in escrow until confirmation
+    pub escrow_payments: bool,
+}
+
+impl ClaimTerms {
+    /// Terms for insuring `payment_amount` against `provider_bond`
+    ///
+    /// Snapshotting keeps later config or policy changes from affecting the claim.
+    pub fn new(
+        config: &InsuranceConfig,
+        provider_bond: &ProviderBond,
+        policy: Option<&ProviderPolicy>,
+        payment_amount: u64,
+    ) -> Result<Self> {
+        let coverage_multiplier_bps = provider_bond.effective_coverage_multiplier_bps(config);
+        config.validate_coverage_multiplier(coverage_multiplier_bps)?;
+        let coverage = apply_bps(payment_amount, coverage_multiplier_bps)?;
+
+        // An escrowed payment refunds itself, so the bond only covers the rest
+        let escrow_payments = policy.is_some_and(|policy| policy.escrow_payments);
+        let locked_amount = if escrow_payments {
+            coverage
+                .checked_sub(payment_amount)
+                .ok_or(InsuranceError::ArithmeticOverflow)?
+        } else {
+            coverage
+        };
+
+        // Coverage above refund + penalty is buffer that unlocks with the claim
+        let penalty_amount = apply_bps(payment_amount, config.slash_rate_bps(SlashReason::Timeout))?;
+        let refund_amount = if escrow_payments { 0 } else { payment_amount };
+        require!(
+            refund_amount
+                .checked_add(penalty_amount)
+                .is_some_and(|payout| payout <= locked_amount),
+            InsuranceError::InvalidCoverageMultiplier
+        );
+
+        let arbiter = policy
+            .map(|policy| policy.arbiter)
+            .filter(|arbiter| *arbiter != Pubkey::default())
+            .unwrap_or(config.arbiter);
+        let dispute_window = if arbiter == Pubkey::default() {
+            0
+        } else {
+            config.dispute_window
+        };
+        let payout_schedule = policy
+            .map(|policy| policy.payout_schedule.clone())
+            .unwrap_or_default();
+
+        Ok(Self {
+            locked_amount,
+            refund_amount,
+            penalty_amount,
+            arbiter,
+            dispute_window,
+            payout_schedule,
+            escrow_payments,
+        })
+    }
+}
+
+/// One step of a provider's latency-tiered payout schedule
+///
+/// Delivery confirmed within `max_latency_seconds` of purchase owes the client
+/// `payout_bps` of the claim's timeout payout. Tiers are ordered by latency and
+/// delivery slower than the last tier owes that tier's payout.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PayoutTier {
+    /// Latency (seconds since purchase) this tier covers up to
+    pub max_latency_seconds: u64,
+    /// Share of the refund and penalty owed (in basis points)
+    pub payout_bps: u16,
+}
+
+impl PayoutTier {
+    /// Most tiers a schedule can hold
+    pub const MAX_TIERS: usize = 4;
+
+    pub const LEN: usize = 8 + // max_latency_seconds
+        2;   // payout_bps
+
+    /// Check tiers are bounded, ordered by latency and never pay less when slower
+    pub fn validate_schedule(schedule: &[PayoutTier]) -> Result<()> {
+        require!(schedule.len() <= Self::MAX_TIERS, InsuranceError::InvalidPolicy);
+        require!(
+            schedule.iter().all(|tier| tier.payout_bps as u64 <= BPS_DENOMINATOR),
+            InsuranceError::InvalidPolicy
+        );
+        require!(
+            schedule.windows(2).all(|pair| {
+                pair[0].max_latency_seconds < pair[1].max_latency_seconds
+                    && pair[0].payout_bps <= pair[1].payout_bps
+            }),
+            InsuranceError::InvalidPolicy
+        );
+        Ok(())
+    }
+
+    /// Payout owed for delivery `latency_seconds` after purchase
+    pub fn payout_bps_for(schedule: &[PayoutTier], latency_seconds: u64) -> u16 {
+        schedule.iter()
+            .find(|tier| latency_seconds <= tier.max_latency_seconds)
+            .or(schedule.last())
+            .map_or(0, |tier| tier.payout_bps)
+    }
+}
+
+/// One request in `purchase_insurance_batch`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchPurchaseItem {
+    pub request_commitment: [u8; 32],
+    /// Provider's 402 challenge the commitment is derived from
+    pub challenge: [u8; 32],
+    pub payment_amount: u64,
+}
+
+/// One claim in `confirm_service_batch`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchConfirmItem {
+    pub request_commitment: [u8; 32],
+    /// Share of the service delivered (10000 for a full response)
+    pub delivered_fraction_bps: u16,
 }
 
 /// Insurance claim account (one per request)
@@ -66,14 +811,37 @@ pub struct InsuranceClaim {
     pub client: Pubkey,
     /// Provider who must fulfill service
     pub provider: Pubkey,
+    /// Token mint of the payment and the backing bond
+    pub mint: Pubkey,
+    /// Paid the claim's rent; receives it back when the claim is closed
+    pub payer: Pubkey,
     /// Payment amount in lamports
     pub payment_amount: u64,
-    /// Locked amount (payment_amount * 1.02)
+    /// Bond locked at purchase (payment_amount * coverage multiplier, less any escrow)
     pub locked_amount: u64,
+    /// Refunded to the client from the bond on a timeout claim (snapshotted at purchase)
+    pub refund_amount: u64,
+    /// Paid to the platform on a timeout claim (snapshotted at purchase)
+    pub penalty_amount: u64,
     /// Deadline timestamp (Unix seconds)
     pub deadline: i64,
     /// Current status
     pub status: ClaimStatus,
+    /// Resolves a dispute on this claim (default if disputes are disabled)
+    pub arbiter: Pubkey,
+    /// Dispute window snapshotted at purchase (in seconds, 0 disables)
+    pub dispute_window: u64,
+    /// End of the dispute window while the bond stays locked after confirmation,
+    /// then of the arbiter's time to rule once disputed
+    pub dispute_deadline: i64,
+    /// Purchase timestamp (Unix seconds), the origin for latency tiers
+    pub created_at: i64,
+    /// Provider's payout schedule snapshotted at purchase
+    pub payout_schedule: Vec<PayoutTier>,
+    /// Share of the refund and penalty paid out so far (in basis points)
+    pub payout_bps: u16,
+    /// Client payment still held in the vault (0 when paid up front)
+    pub escrow_amount: u64,
     /// PDA bump
     pub bump: u8,
 }
@@ -83,11 +851,179 @@ impl InsuranceClaim {
         32 + // request_commitment
         32 + // client
         32 + // provider
+        32 + // mint
+        32 + // payer
         8 +  // payment_amount
         8 +  // locked_amount
+        8 +  // refund_amount
+        8 +  // penalty_amount
         8 +  // deadline
         1 +  // status
+        32 + // arbiter
+        8 +  // dispute_window
+        8 +  // dispute_deadline
+        8 +  // created_at
+        4 + PayoutTier::LEN * PayoutTier::MAX_TIERS + // payout_schedule
+        2 +  // payout_bps
+        8 +  // escrow_amount
+        1;   // bump
+
+    /// Domain separator for request commitments
+    pub const COMMITMENT_DOMAIN: &'static [u8] = b"x402-insurance:commitment:v1";
+
+    /// Request commitment binding a provider's 402 challenge to one client
+    pub fn request_commitment_for(
+        provider: &Pubkey,
+        client: &Pubkey,
+        challenge: &[u8; 32],
+    ) -> [u8; 32] {
+        hashv(&[
+            Self::COMMITMENT_DOMAIN,
+            provider.as_ref(),
+            client.as_ref(),
+            challenge,
+        ])
+        .to_bytes()
+    }
+
+    /// Whether `address` is this claim's PDA under the client/provider scoped seeds
+    pub fn has_canonical_address(&self, address: &Pubkey) -> bool {
+        Pubkey::create_program_address(
+            &[
+                b"claim",
+                self.client.as_ref(),
+                self.provider.as_ref(),
+                &self.request_commitment,
+                &[self.bump],
+            ],
+            &crate::ID,
+        )
+        .is_ok_and(|key| key == *address)
+    }
+
+    /// Refund and penalty owed once `payout_bps` of the timeout payout is due
+    pub fn payout_for(&self, payout_bps: u16) -> Result<(u64, u64)> {
+        Ok((
+            apply_bps(self.refund_amount, payout_bps)?,
+            apply_bps(self.penalty_amount, payout_bps)?,
+        ))
+    }
+
+    /// Bond still locked for this claim: the locked amount less what was paid out
+    pub fn locked_remaining(&self) -> Result<u64> {
+        let (refund_paid, penalty_paid) = self.payout_for(self.payout_bps)?;
+        self.locked_amount
+            .checked_sub(refund_paid)
+            .and_then(|v| v.checked_sub(penalty_paid))
+            .ok_or(error!(InsuranceError::ArithmeticOverflow))
+    }
+
+    /// Whether the claim is final and no longer holds any bond or escrow
+    pub fn is_settled(&self) -> bool {
+        if self.escrow_amount > 0 {
+            return false;
+        }
+        match self.status {
+            ClaimStatus::Pending | ClaimStatus::Disputed => false,
+            ClaimStatus::Confirmed => self.dispute_deadline == 0,
+            ClaimStatus::Claimed => true,
+        }
+    }
+}
+
+/// Prepaid session covering many requests between one client and provider
+///
+/// The client prepays `price_per_request * max_requests` to the provider and
+/// the provider's bond is locked once for the whole budget. Requests are only
+/// counted and folded into `request_hash`; on close, every request the
+/// provider did not confirm as served is refunded out of the locked bond.
+#[account]
+pub struct Session {
+    /// Client who prepaid the session
+    pub client: Pubkey,
+    /// Provider serving the session
+    pub provider: Pubkey,
+    /// Token mint of the payment and the backing bond
+    pub mint: Pubkey,
+    /// Client-chosen nonce distinguishing sessions with the same provider
+    pub nonce: u64,
+    /// Price of one request
+    pub price_per_request: u64,
+    /// Requests the budget covers
+    pub max_requests: u64,
+    /// Requests recorded so far
+    pub request_count: u64,
+    /// Requests the provider confirmed as served
+    pub served_count: u64,
+    /// Rolling hash of every recorded request commitment
+    pub request_hash: [u8; 32],
+    /// Bond locked for the whole budget (budget * coverage multiplier)
+    pub locked_amount: u64,
+    /// Penalty rate on failed requests (snapshotted at open)
+    pub penalty_rate_bps: u16,
+    /// No requests can be recorded after this (Unix seconds)
+    pub expires_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl Session {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // client
+        32 + // provider
+        32 + // mint
+        8 +  // nonce
+        8 +  // price_per_request
+        8 +  // max_requests
+        8 +  // request_count
+        8 +  // served_count
+        32 + // request_hash
+        8 +  // locked_amount
+        2 +  // penalty_rate_bps
+        8 +  // expires_at
         1;   // bump
+
+    /// Longest allowed session (30 days)
+    pub const MAX_DURATION: u64 = 30 * 24 * 60 * 60;
+
+    /// Domain separator for the rolling request hash
+    pub const REQUEST_HASH_DOMAIN: &'static [u8] = b"x402-insurance:session:v1";
+
+    /// Fold a request commitment into the rolling hash
+    pub fn record_request(&mut self, request_commitment: &[u8; 32]) -> Result<()> {
+        require!(self.request_count < self.max_requests, InsuranceError::SessionBudgetExhausted);
+        self.request_hash = hashv(&[
+            Self::REQUEST_HASH_DOMAIN,
+            &self.request_hash,
+            request_commitment,
+        ])
+        .to_bytes();
+        self.request_count = self.request_count
+            .checked_add(1)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Refund and penalty owed on close
+    ///
+    /// Every request not served is refunded at its price; requests that were
+    /// recorded but never served also pay the penalty.
+    pub fn settlement(&self) -> Result<(u64, u64)> {
+        let unserved = self.max_requests
+            .checked_sub(self.served_count)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        let failed = self.request_count
+            .checked_sub(self.served_count)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        let refund_amount = unserved
+            .checked_mul(self.price_per_request)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        let failed_amount = failed
+            .checked_mul(self.price_per_request)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        let penalty_amount = apply_bps(failed_amount, self.penalty_rate_bps)?;
+        Ok((refund_amount, penalty_amount))
+    }
 }
 
 /// Status of an insurance claim
@@ -99,4 +1035,53 @@ pub enum ClaimStatus {
     Confirmed,
     /// Claimed - client claimed compensation after timeout
     Claimed,
+    /// Disputed - client contested a confirmation, awaiting the arbiter
+    Disputed,
+}
+
+/// Arbiter's decision on a disputed claim
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeRuling {
+    /// Delivery stands; the provider's bond is unlocked
+    Provider,
+    /// Delivery failed; the client is refunded from the bond as on a timeout
+    /// (less any partial payout already made at confirmation)
+    Client,
+    /// The provider's delivery proof was invalid; the client is refunded as on
+    /// a `Client` ruling and the provider slashed at the invalid-signature rate
+    InvalidProof,
+}
+
+impl DisputeRuling {
+    /// Applied when the arbiter does not rule by the claim's dispute deadline,
+    /// so a provider gains nothing from naming an arbiter that never rules
+    pub const DEFAULT: DisputeRuling = DisputeRuling::Client;
+
+    /// Slash reason when the ruling goes against the provider
+    pub fn slash_reason(&self) -> Option<SlashReason> {
+        match self {
+            DisputeRuling::Provider => None,
+            DisputeRuling::Client => Some(SlashReason::DisputeLoss),
+            DisputeRuling::InvalidProof => Some(SlashReason::InvalidSignature),
+        }
+    }
+}
+
+/// Why a provider's bond was slashed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SlashReason {
+    /// Timeout penalty, including the penalty share of late or partial delivery
+    /// and of unserved session requests
+    Timeout,
+    /// Lost a dispute on a `Client` ruling
+    DisputeLoss,
+    /// Arbiter found the delivery proof invalid
+    InvalidSignature,
+    /// Bond seized by a liquidation
+    Liquidation,
+}
+
+impl SlashReason {
+    /// Number of reasons (length of the per-reason ledger)
+    pub const COUNT: usize = 4;
 }
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Assign};
+
+use crate::errors::InsuranceError;
+use crate::events::ClaimClosed;
+use crate::state::InsuranceClaim;
+
+/// Seed prefix of the marker PDA left behind when a claim is closed
+pub const CLAIM_TOMBSTONE_SEED: &[u8] = b"claim_tombstone";
+
+/// Whether the claim this tombstone is derived from has already been closed
+///
+/// Only this program can assign the PDA to itself, so ownership is the marker;
+/// lamports sent to the address by anyone else do not count.
+pub fn is_tombstoned(claim_tombstone: &AccountInfo) -> bool {
+    claim_tombstone.owner == &crate::ID
+}
+
+/// Close a settled claim and return its rent to the payer
+///
+/// A zero-data tombstone PDA owned by this program is funded from the claim's
+/// rent first, so the claim address (and with it the request commitment) can
+/// never be initialized again.
+pub fn close_claim<'info>(
+    claim: &mut Account<'info, InsuranceClaim>,
+    claim_tombstone: &AccountInfo<'info>,
+    claim_tombstone_bump: u8,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    require!(claim.is_settled(), InsuranceError::ClaimNotSettled);
+    require!(!is_tombstoned(claim_tombstone), InsuranceError::CommitmentAlreadyUsed);
+
+    // Fund the tombstone out of the claim's rent (topping up any stray lamports)
+    let claim_info = claim.to_account_info();
+    let tombstone_rent = Rent::get()?
+        .minimum_balance(0)
+        .saturating_sub(claim_tombstone.lamports());
+    if tombstone_rent > 0 {
+        let claim_lamports = claim_info.lamports()
+            .checked_sub(tombstone_rent)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        let tombstone_lamports = claim_tombstone.lamports()
+            .checked_add(tombstone_rent)
+            .ok_or(InsuranceError::ArithmeticOverflow)?;
+        **claim_info.try_borrow_mut_lamports()? = claim_lamports;
+        **claim_tombstone.try_borrow_mut_lamports()? = tombstone_lamports;
+    }
+
+    let claim_key = claim.key();
+    let seeds = &[
+        CLAIM_TOMBSTONE_SEED,
+        claim_key.as_ref(),
+        &[claim_tombstone_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = Assign {
+        account_to_assign: claim_tombstone.clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer);
+    system_program::assign(cpi_ctx, &crate::ID)?;
+
+    let rent_refunded = claim_info.lamports();
+    claim.close(payer.clone())?;
+
+    emit!(ClaimClosed {
+        claim: claim_key,
+        request_commitment: claim.request_commitment,
+        payer: payer.key(),
+        rent_refunded,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Close a claim through the optional accounts an instruction takes for
+/// `close_claim = true`
+pub fn close_requested_claim<'info>(
+    claim: &mut Account<'info, InsuranceClaim>,
+    claim_tombstone: Option<&UncheckedAccount<'info>>,
+    claim_tombstone_bump: Option<u8>,
+    payer: Option<&SystemAccount<'info>>,
+    system_program: Option<&Program<'info, System>>,
+) -> Result<()> {
+    let (Some(claim_tombstone), Some(claim_tombstone_bump), Some(payer), Some(system_program)) =
+        (claim_tombstone, claim_tombstone_bump, payer, system_program)
+    else {
+        return err!(InsuranceError::MissingCloseAccounts);
+    };
+    close_claim(claim, claim_tombstone, claim_tombstone_bump, payer, system_program)
+}
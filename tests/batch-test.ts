@@ -0,0 +1,418 @@
+/**
+ * Batch Purchase / Confirm Test
+ *
+ * Test Scenario:
+ * 1. Client insures several requests in one purchase_insurance_batch
+ * 2. Provider confirms them in one confirm_service_batch, with one Ed25519
+ *    delivery proof per claim
+ * 3. Compute-unit benchmark: grow each batch until the transaction exceeds the
+ *    1232-byte packet limit and report the compute units used per size
+ */
+
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { X402Insurance } from "../target/types/x402_insurance";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  ComputeBudgetProgram,
+  Transaction,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  deliveryProofMessage,
+  ed25519Instruction,
+  requestCommitmentFor,
+} from "./helpers";
+
+describe("Batch purchase and confirm", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.X402Insurance as Program<X402Insurance>;
+
+  const PAYMENT = 10_000; // 0.01 USDC per call
+  const MAX_COMPUTE_UNITS = 1_400_000;
+  const MAX_PACKET_SIZE = 1232;
+
+  let mint: PublicKey;
+  let bondProvider: Keypair;
+  let client: Keypair;
+  let configPDA: PublicKey;
+  let vaultPDA: PublicKey;
+  let providerBondPDA: PublicKey;
+  let policyPDA: PublicKey;
+  let providerTokenAccount: PublicKey;
+  let clientTokenAccount: PublicKey;
+  let treasuryTokenAccount: PublicKey;
+  let nextChallenge = 0;
+
+  // Fresh items with their claim and tombstone PDAs
+  const newItems = (count: number) =>
+    Array.from({ length: count }, () => {
+      const challenge = Buffer.alloc(32);
+      challenge.writeUInt32LE(nextChallenge++);
+      const requestCommitment = requestCommitmentFor(
+        bondProvider.publicKey,
+        client.publicKey,
+        challenge
+      );
+      const [claim] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("claim"),
+          client.publicKey.toBuffer(),
+          bondProvider.publicKey.toBuffer(),
+          requestCommitment,
+        ],
+        program.programId
+      );
+      const [tombstone] = PublicKey.findProgramAddressSync(
+        [Buffer.from("claim_tombstone"), claim.toBuffer()],
+        program.programId
+      );
+      return { challenge, requestCommitment, claim, tombstone };
+    });
+
+  type Item = ReturnType<typeof newItems>[number];
+
+  const purchaseIxs = async (items: Item[]) => [
+    await program.methods
+      .purchaseInsuranceBatch(
+        items.map((item) => ({
+          requestCommitment: Array.from(item.requestCommitment),
+          challenge: Array.from(item.challenge),
+          paymentAmount: new anchor.BN(PAYMENT),
+        })),
+        new anchor.BN(5)
+      )
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        providerPolicy: policyPDA,
+        client: client.publicKey,
+        clientTokenAccount,
+        provider: bondProvider.publicKey,
+        providerTokenAccount,
+        mint,
+        vault: vaultPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(
+        items.flatMap((item) => [
+          { pubkey: item.claim, isWritable: true, isSigner: false },
+          { pubkey: item.tombstone, isWritable: false, isSigner: false },
+        ])
+      )
+      .instruction(),
+  ];
+
+  // Delivery proofs signed by `signer` (the provider unless testing forgeries),
+  // followed by the batch confirmation. Claims not purchased yet, when only
+  // measuring size, sign placeholder terms of the same length.
+  const confirmIxs = async (items: Item[], signer: Keypair = bondProvider) => {
+    const claims = await program.account.insuranceClaim.fetchMultiple(
+      items.map((item) => item.claim)
+    );
+    const proofs = items.map((item, i) =>
+      deliveryProofMessage(
+        program.programId,
+        item.claim,
+        item.requestCommitment,
+        claims[i]?.paymentAmount ?? new anchor.BN(PAYMENT),
+        claims[i]?.deadline ?? new anchor.BN(0)
+      )
+    );
+    const confirmIx = await program.methods
+      .confirmServiceBatch(
+        items.map((item) => ({
+          requestCommitment: Array.from(item.requestCommitment),
+          deliveredFractionBps: 10_000,
+        }))
+      )
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        provider: bondProvider.publicKey,
+        providerTokenAccount,
+        vault: vaultPDA,
+        clientTokenAccount,
+        platformTreasuryTokenAccount: treasuryTokenAccount,
+        mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .remainingAccounts(
+        items.map((item) => ({
+          pubkey: item.claim,
+          isWritable: true,
+          isSigner: false,
+        }))
+      )
+      .instruction();
+    return [ed25519Instruction(signer, proofs), confirmIx];
+  };
+
+  // Serialized size of a transaction, or null if it exceeds the packet limit
+  const packetSize = (ixs: TransactionInstruction[], feePayer: Keypair) => {
+    const tx = new Transaction().add(
+      ComputeBudgetProgram.setComputeUnitLimit({ units: MAX_COMPUTE_UNITS }),
+      ...ixs
+    );
+    tx.feePayer = feePayer.publicKey;
+    tx.recentBlockhash = PublicKey.default.toBase58();
+    try {
+      tx.sign(feePayer);
+      const bytes = tx.serialize().length;
+      return bytes <= MAX_PACKET_SIZE ? bytes : null;
+    } catch {
+      return null;
+    }
+  };
+
+  // Send with the maximum CU limit and return the units consumed
+  const sendMeasured = async (ixs: TransactionInstruction[], signer: Keypair) => {
+    const tx = new Transaction().add(
+      ComputeBudgetProgram.setComputeUnitLimit({ units: MAX_COMPUTE_UNITS }),
+      ...ixs
+    );
+    const signature = await provider.sendAndConfirm(tx, [signer], {
+      commitment: "confirmed",
+    });
+    const confirmed = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    return confirmed.meta.computeUnitsConsumed;
+  };
+
+  before(async () => {
+    bondProvider = Keypair.generate();
+    client = Keypair.generate();
+    for (const wallet of [bondProvider, client]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(
+          wallet.publicKey,
+          10 * anchor.web3.LAMPORTS_PER_SOL
+        )
+      );
+    }
+
+    mint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      provider.wallet.publicKey,
+      null,
+      6
+    );
+
+    [configPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    [vaultPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), mint.toBuffer()],
+      program.programId
+    );
+    [providerBondPDA] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("provider_bond"),
+        bondProvider.publicKey.toBuffer(),
+        mint.toBuffer(),
+      ],
+      program.programId
+    );
+    [policyPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("provider_policy"), bondProvider.publicKey.toBuffer()],
+      program.programId
+    );
+
+    // Initialize the protocol if this suite runs first
+    let config = await program.account.insuranceConfig.fetchNullable(configPDA);
+    if (!config) {
+      await program.methods
+        .initialize(200, new anchor.BN(300), new anchor.BN(86400), new anchor.BN(0))
+        .accounts({
+          config: configPDA,
+          platformTreasury: provider.wallet.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      config = await program.account.insuranceConfig.fetch(configPDA);
+    }
+
+    providerTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        bondProvider,
+        mint,
+        bondProvider.publicKey
+      )
+    ).address;
+    clientTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        client,
+        mint,
+        client.publicKey
+      )
+    ).address;
+    treasuryTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        config.platformTreasury,
+        true
+      )
+    ).address;
+
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      providerTokenAccount,
+      provider.wallet.publicKey,
+      100_000_000
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      clientTokenAccount,
+      provider.wallet.publicKey,
+      100_000_000
+    );
+
+    await program.methods
+      .depositBond(new anchor.BN(50_000_000))
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        provider: bondProvider.publicKey,
+        providerTokenAccount,
+        mint,
+        vault: vaultPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([bondProvider])
+      .rpc();
+  });
+
+  it("Purchases and confirms several claims in one instruction each", async () => {
+    const items = newItems(3);
+    const bondBefore = await program.account.providerBond.fetch(providerBondPDA);
+
+    await provider.sendAndConfirm(new Transaction().add(...(await purchaseIxs(items))), [
+      client,
+    ]);
+
+    let bond = await program.account.providerBond.fetch(providerBondPDA);
+    const claims = await program.account.insuranceClaim.fetchMultiple(
+      items.map((item) => item.claim)
+    );
+    const totalLocked = claims.reduce(
+      (sum, claim) => sum + claim.lockedAmount.toNumber(),
+      0
+    );
+    claims.forEach((claim) => assert.deepEqual(claim.status, { pending: {} }));
+    assert.equal(
+      bond.lockedBond.toNumber(),
+      bondBefore.lockedBond.toNumber() + totalLocked
+    );
+    assert.equal(bond.activeClaims, bondBefore.activeClaims + items.length);
+
+    // The same commitment cannot be insured twice
+    try {
+      await provider.sendAndConfirm(
+        new Transaction().add(...(await purchaseIxs([items[0]]))),
+        [client]
+      );
+      assert.fail("Existing claim should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "ClaimAlreadyExists");
+    }
+
+    // Every delivery needs the provider's own proof
+    try {
+      await provider.sendAndConfirm(
+        new Transaction().add(...(await confirmIxs(items, client))),
+        [bondProvider]
+      );
+      assert.fail("Proofs signed by someone else should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidSignature");
+    }
+    try {
+      const [, confirmIx] = await confirmIxs(items);
+      const [shortProofIx] = await confirmIxs(items.slice(1));
+      await provider.sendAndConfirm(new Transaction().add(shortProofIx, confirmIx), [
+        bondProvider,
+      ]);
+      assert.fail("A batch missing a proof should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "MalformedEd25519Instruction");
+    }
+
+    await provider.sendAndConfirm(new Transaction().add(...(await confirmIxs(items))), [
+      bondProvider,
+    ]);
+
+    bond = await program.account.providerBond.fetch(providerBondPDA);
+    const confirmed = await program.account.insuranceClaim.fetchMultiple(
+      items.map((item) => item.claim)
+    );
+    confirmed.forEach((claim) => assert.deepEqual(claim.status, { confirmed: {} }));
+    assert.equal(bond.lockedBond.toNumber(), bondBefore.lockedBond.toNumber());
+    assert.equal(bond.activeClaims, bondBefore.activeClaims);
+
+    console.log("✅ Batch of", items.length, "purchased and confirmed");
+  });
+
+  it("Benchmarks how many claims fit in one transaction", async () => {
+    const results = [];
+    for (const [name, buildIxs, signer] of [
+      ["purchase_insurance_batch", purchaseIxs, client],
+      ["confirm_service_batch", confirmIxs, bondProvider],
+    ] as const) {
+      let largest = 0;
+      for (let size = 1; ; size++) {
+        const items = newItems(size);
+        const bytes = packetSize(await buildIxs(items), signer);
+        if (bytes === null) {
+          break;
+        }
+
+        // Confirmation needs the claims to be pending first, and signs their terms
+        if (name === "confirm_service_batch") {
+          for (let i = 0; i < items.length; i += 2) {
+            await provider.sendAndConfirm(
+              new Transaction().add(...(await purchaseIxs(items.slice(i, i + 2)))),
+              [client]
+            );
+          }
+        }
+
+        const units = await sendMeasured(await buildIxs(items), signer);
+        results.push({ instruction: name, claims: size, bytes, units });
+        largest = size;
+      }
+      assert.isAbove(largest, 1, `${name} should fit more than one claim`);
+    }
+
+    console.table(results);
+  });
+});
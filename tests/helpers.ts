@@ -0,0 +1,187 @@
+/**
+ * Helpers shared by the test suites: canonical signed messages, request
+ * commitments, multi-signature Ed25519 instructions, waiting on the cluster
+ * clock and an `update_config` argument that changes nothing.
+ */
+
+import * as anchor from "@coral-xyz/anchor";
+import {
+  Ed25519Program,
+  Keypair,
+  PublicKey,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import { createHash } from "crypto";
+
+// Request commitment binding a provider's 402 challenge to one client
+// (see InsuranceClaim::request_commitment_for)
+export function requestCommitmentFor(
+  provider: PublicKey,
+  client: PublicKey,
+  challenge: Buffer
+): Buffer {
+  return createHash("sha256")
+    .update(
+      Buffer.concat([
+        Buffer.from("x402-insurance:commitment:v1"),
+        provider.toBuffer(),
+        client.toBuffer(),
+        challenge,
+      ])
+    )
+    .digest();
+}
+
+// Canonical payment quote signed by the provider (see ed25519::payment_quote_message)
+export function paymentQuoteMessage(
+  programId: PublicKey,
+  requestCommitment: Buffer,
+  mint: PublicKey,
+  price: anchor.BN,
+  maxTimeoutSeconds: anchor.BN,
+  expirySlot: anchor.BN
+): Buffer {
+  return Buffer.concat([
+    Buffer.from("x402-insurance:quote:v1"),
+    programId.toBuffer(),
+    requestCommitment,
+    mint.toBuffer(),
+    price.toArrayLike(Buffer, "le", 8),
+    maxTimeoutSeconds.toArrayLike(Buffer, "le", 8),
+    expirySlot.toArrayLike(Buffer, "le", 8),
+  ]);
+}
+
+// Canonical delivery proof signed by the provider (see ed25519::delivery_proof_message)
+export function deliveryProofMessage(
+  programId: PublicKey,
+  claim: PublicKey,
+  requestCommitment: Buffer,
+  paymentAmount: anchor.BN,
+  deadline: anchor.BN
+): Buffer {
+  return Buffer.concat([
+    Buffer.from("x402-insurance:delivery:v1"),
+    programId.toBuffer(),
+    claim.toBuffer(),
+    requestCommitment,
+    paymentAmount.toArrayLike(Buffer, "le", 8),
+    deadline.toTwos(64).toArrayLike(Buffer, "le", 8),
+  ]);
+}
+
+// Canonical delivery receipt signed by the client (see ed25519::delivery_receipt_message)
+export function deliveryReceiptMessage(
+  programId: PublicKey,
+  claim: PublicKey,
+  requestCommitment: Buffer,
+  paymentAmount: anchor.BN,
+  deadline: anchor.BN
+): Buffer {
+  return Buffer.concat([
+    Buffer.from("x402-insurance:receipt:v1"),
+    programId.toBuffer(),
+    claim.toBuffer(),
+    requestCommitment,
+    paymentAmount.toArrayLike(Buffer, "le", 8),
+    deadline.toTwos(64).toArrayLike(Buffer, "le", 8),
+  ]);
+}
+
+// Canonical session receipt signed by the client (see ed25519::session_receipt_message)
+export function sessionReceiptMessage(
+  programId: PublicKey,
+  session: PublicKey,
+  servedCount: anchor.BN
+): Buffer {
+  return Buffer.concat([
+    Buffer.from("x402-insurance:session-receipt:v1"),
+    programId.toBuffer(),
+    session.toBuffer(),
+    servedCount.toArrayLike(Buffer, "le", 8),
+  ]);
+}
+
+// One Ed25519 program instruction verifying `signer`'s signature over each
+// message, in order, with all data inline (as ed25519::load_preceding_signatures
+// requires)
+export function ed25519Instruction(
+  signer: Keypair,
+  messages: Buffer[]
+): TransactionInstruction {
+  const HEADER_SIZE = 2;
+  const OFFSETS_SIZE = 14;
+  const THIS_INSTRUCTION = 0xffff;
+
+  const header = Buffer.alloc(HEADER_SIZE + messages.length * OFFSETS_SIZE);
+  header.writeUInt8(messages.length, 0);
+  const payloads: Buffer[] = [];
+  let cursor = header.length;
+  messages.forEach((message, i) => {
+    // Reuse web3.js signing: its single-signature layout puts the pubkey at
+    // 16..48 and the signature at 48..112
+    const single = Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: signer.secretKey,
+      message,
+    });
+    const publicKeyOffset = cursor;
+    const signatureOffset = publicKeyOffset + 32;
+    const messageOffset = signatureOffset + 64;
+
+    const at = HEADER_SIZE + i * OFFSETS_SIZE;
+    header.writeUInt16LE(signatureOffset, at);
+    header.writeUInt16LE(THIS_INSTRUCTION, at + 2);
+    header.writeUInt16LE(publicKeyOffset, at + 4);
+    header.writeUInt16LE(THIS_INSTRUCTION, at + 6);
+    header.writeUInt16LE(messageOffset, at + 8);
+    header.writeUInt16LE(message.length, at + 10);
+    header.writeUInt16LE(THIS_INSTRUCTION, at + 12);
+
+    payloads.push(single.data.subarray(16, 48), single.data.subarray(48, 112), message);
+    cursor = messageOffset + message.length;
+  });
+
+  return new TransactionInstruction({
+    programId: Ed25519Program.programId,
+    keys: [],
+    data: Buffer.concat([header, ...payloads]),
+  });
+}
+
+// Wait until the cluster clock reaches `unixTimestamp` (deadlines are checked
+// against on-chain time, not the local clock)
+export async function waitForChainTime(
+  connection: anchor.web3.Connection,
+  unixTimestamp: number
+): Promise<void> {
+  for (;;) {
+    const blockTime = await connection.getBlockTime(await connection.getSlot());
+    if (blockTime !== null && blockTime >= unixTimestamp) {
+      return;
+    }
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+  }
+}
+
+// `update_config` params leaving every field unchanged; spread and override
+export const noConfigChanges = {
+  platformTreasury: null,
+  platformPenaltyRate: null,
+  coverageMultiplierBps: null,
+  defaultTimeout: null,
+  liquidationGracePeriod: null,
+  defaultMinBond: null,
+  disputeWindow: null,
+  arbiter: null,
+  crankDelay: null,
+  crankTipBps: null,
+  liquidationBonusBps: null,
+  maxLiquidationBonusBps: null,
+  liquidationBonusRamp: null,
+  reinstatementBondBps: null,
+  reinstatementCooldown: null,
+  reinstatementRequiresApproval: null,
+  liquidationPenaltyBps: null,
+  disputeLossPenaltyBps: null,
+  invalidSignaturePenaltyBps: null,
+};
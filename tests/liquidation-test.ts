@@ -3,9 +3,11 @@
  *
  * Test Scenario:
  * 1. Provider deposits bond
- * 2. Multiple claims reduce Provider's bond below minimum
- * 3. After 24-hour grace period, liquidate Provider
- * 4. Remaining bond transferred to platform
+ * 2. Multiple timed-out claims reduce Provider's bond below minimum
+ * 3. Liquidation is rejected until the grace period has passed, and again
+ *    after a pause restarts it
+ * 4. After the grace period, liquidate Provider
+ * 5. Remaining bond transferred to the liquidator (bonus) and the platform
  */
 
 import * as anchor from "@coral-xyz/anchor";
@@ -15,16 +17,16 @@ import {
   PublicKey,
   Keypair,
   SystemProgram,
-  LAMPORTS_PER_SOL,
+  SYSVAR_RENT_PUBKEY,
 } from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
+  createMint,
   getOrCreateAssociatedTokenAccount,
-  getAccount,
+  mintTo,
 } from "@solana/spl-token";
 import { assert } from "chai";
-import * as fs from "fs";
-import * as path from "path";
+import { noConfigChanges, requestCommitmentFor, waitForChainTime } from "./helpers";
 
 describe("Provider Liquidation Test", () => {
   const provider = anchor.AnchorProvider.env();
@@ -32,198 +34,349 @@ describe("Provider Liquidation Test", () => {
 
   const program = anchor.workspace.X402Insurance as Program<X402Insurance>;
 
-  // Token mint
-  const TOKEN_MINT = new PublicKey("4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU");
+  const BOND_AMOUNT = new anchor.BN(2_500_000); // 2.5 tokens
+  const MIN_BOND = new anchor.BN(1_000_000); // 1 token
+  const PAYMENT_AMOUNT = new anchor.BN(1_000_000); // 1 token per claim
+  const GRACE_PERIOD = 5; // seconds, shortened for the test
 
-  // Test keypairs
+  let mint: PublicKey;
   let providerKeypair: Keypair;
   let clientKeypair: Keypair;
+  let liquidatorKeypair: Keypair;
 
   // PDAs
   let configPDA: PublicKey;
   let vaultPDA: PublicKey;
-  let vaultTokenAccount: PublicKey;
   let providerBondPDA: PublicKey;
 
   // Token accounts
-  let providerTokenAccount: any;
-  let platformTokenAccount: any;
-
-  before(async () => {
-    console.log("\n🔧 Setting up Provider liquidation test environment...\n");
-
-    // Read keypairs
-    const keysDir = path.join(__dirname, "../.keys");
-    const providerPath = path.join(keysDir, "provider.json");
-    const clientPath = path.join(keysDir, "client.json");
-
-    const providerSecretKey = Uint8Array.from(
-      JSON.parse(fs.readFileSync(providerPath, "utf-8"))
+  let providerTokenAccount: PublicKey;
+  let clientTokenAccount: PublicKey;
+  let liquidatorTokenAccount: PublicKey;
+  let platformTokenAccount: PublicKey;
+
+  const balance = async (account: PublicKey) =>
+    Number((await provider.connection.getTokenAccountBalance(account)).value.amount);
+
+  const newClaim = (seed: number) => {
+    const challenge = Buffer.alloc(32, seed);
+    const requestCommitment = requestCommitmentFor(
+      providerKeypair.publicKey,
+      clientKeypair.publicKey,
+      challenge
     );
-    const clientSecretKey = Uint8Array.from(
-      JSON.parse(fs.readFileSync(clientPath, "utf-8"))
+    const [claim] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("claim"),
+        clientKeypair.publicKey.toBuffer(),
+        providerKeypair.publicKey.toBuffer(),
+        requestCommitment,
+      ],
+      program.programId
     );
+    return { challenge, requestCommitment, claim };
+  };
+
+  const liquidate = () =>
+    program.methods
+      .liquidateProvider()
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        vault: vaultPDA,
+        liquidator: liquidatorKeypair.publicKey,
+        liquidatorTokenAccount,
+        platformTreasuryTokenAccount: platformTokenAccount,
+        mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([liquidatorKeypair])
+      .rpc();
 
-    providerKeypair = Keypair.fromSecretKey(providerSecretKey);
-    clientKeypair = Keypair.fromSecretKey(clientSecretKey);
+  before(async () => {
+    providerKeypair = Keypair.generate();
+    clientKeypair = Keypair.generate();
+    liquidatorKeypair = Keypair.generate();
+    for (const wallet of [providerKeypair, clientKeypair, liquidatorKeypair]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(
+          wallet.publicKey,
+          10 * anchor.web3.LAMPORTS_PER_SOL
+        )
+      );
+    }
 
-    console.log("📋 Test Accounts:");
-    console.log("  Platform:", provider.wallet.publicKey.toString());
-    console.log("  Provider:", providerKeypair.publicKey.toString());
-    console.log("");
+    mint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      provider.wallet.publicKey,
+      null,
+      6
+    );
 
     // Derive PDAs
     [configPDA] = PublicKey.findProgramAddressSync(
       [Buffer.from("config")],
       program.programId
     );
-
     [vaultPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("vault")],
+      [Buffer.from("vault"), mint.toBuffer()],
       program.programId
     );
-
     [providerBondPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("provider_bond"), providerKeypair.publicKey.toBuffer()],
+      [
+        Buffer.from("provider_bond"),
+        providerKeypair.publicKey.toBuffer(),
+        mint.toBuffer(),
+      ],
       program.programId
     );
 
-    // Get token accounts
-    providerTokenAccount = await getOrCreateAssociatedTokenAccount(
-      provider.connection,
-      providerKeypair,
-      TOKEN_MINT,
-      providerKeypair.publicKey
-    );
-
-    const vaultTokenAccountInfo = await getOrCreateAssociatedTokenAccount(
-      provider.connection,
-      providerKeypair,
-      TOKEN_MINT,
-      vaultPDA,
-      true
-    );
-    vaultTokenAccount = vaultTokenAccountInfo.address;
+    // Initialize the protocol if this suite runs first
+    let config = await program.account.insuranceConfig.fetchNullable(configPDA);
+    if (!config) {
+      await program.methods
+        .initialize(200, new anchor.BN(300), new anchor.BN(86400), new anchor.BN(0))
+        .accounts({
+          config: configPDA,
+          platformTreasury: provider.wallet.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      config = await program.account.insuranceConfig.fetch(configPDA);
+    }
 
-    platformTokenAccount = await getOrCreateAssociatedTokenAccount(
-      provider.connection,
-      providerKeypair,
-      TOKEN_MINT,
-      provider.wallet.publicKey
-    );
+    providerTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        providerKeypair,
+        mint,
+        providerKeypair.publicKey
+      )
+    ).address;
+    clientTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        clientKeypair,
+        mint,
+        clientKeypair.publicKey
+      )
+    ).address;
+    liquidatorTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        liquidatorKeypair,
+        mint,
+        liquidatorKeypair.publicKey
+      )
+    ).address;
+    platformTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        config.platformTreasury,
+        true
+      )
+    ).address;
+
+    for (const account of [providerTokenAccount, clientTokenAccount]) {
+      await mintTo(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        account,
+        provider.wallet.publicKey,
+        100_000_000
+      );
+    }
 
-    console.log("✅ Setup complete\n");
+    await program.methods
+      .depositBond(BOND_AMOUNT)
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        provider: providerKeypair.publicKey,
+        providerTokenAccount,
+        mint,
+        vault: vaultPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([providerKeypair])
+      .rpc();
+    await program.methods
+      .setMinBond(MIN_BOND)
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
   });
 
-  it("1️⃣ Check current Provider status", async () => {
-    console.log("🔍 Checking Provider current bond status...");
-
-    try {
-      const bond = await program.account.providerBond.fetch(providerBondPDA);
+  it("1️⃣ Timed-out claims push the Provider below its minimum bond", async () => {
+    const items = [newClaim(0x61), newClaim(0x62)];
+    for (const item of items) {
+      await program.methods
+        .purchaseInsurance(
+          Array.from(item.requestCommitment),
+          Array.from(item.challenge),
+          PAYMENT_AMOUNT,
+          new anchor.BN(1),
+          null
+        )
+        .accounts({
+          config: configPDA,
+          providerBond: providerBondPDA,
+          claim: item.claim,
+          client: clientKeypair.publicKey,
+          clientTokenAccount,
+          provider: providerKeypair.publicKey,
+          providerTokenAccount,
+          mint,
+          vault: vaultPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([clientKeypair])
+        .rpc();
+    }
 
-      console.log("  Provider Bond:");
-      console.log("    Total bond:", bond.totalBond.toString(), "=", bond.totalBond.toNumber() / 1_000_000, "tokens");
-      console.log("    Locked bond:", bond.lockedBond.toString(), "=", bond.lockedBond.toNumber() / 1_000_000, "tokens");
-      console.log("    Minimum bond:", bond.minBond.toString(), "=", bond.minBond.toNumber() / 1_000_000, "tokens");
-      console.log("    Is liquidated:", bond.isLiquidated);
-      console.log("    Undercollateralized since:", bond.undercollateralizedSince.toString());
-      console.log("");
-
-      const available = bond.totalBond.toNumber() - bond.lockedBond.toNumber();
-      console.log("  Available bond:", available / 1_000_000, "tokens");
-
-      if (available < bond.minBond.toNumber()) {
-        console.log("  ⚠️  Provider bond insufficient!");
-      } else {
-        console.log("  ✅ Provider bond sufficient");
-      }
+    const claims = await Promise.all(
+      items.map((item) => program.account.insuranceClaim.fetch(item.claim))
+    );
+    await waitForChainTime(
+      provider.connection,
+      Math.max(...claims.map((claim) => claim.deadline.toNumber()))
+    );
 
-    } catch (error: any) {
-      console.log("  ℹ️  Provider Bond account does not exist");
+    let paidOut = 0;
+    for (const [i, item] of items.entries()) {
+      await program.methods
+        .claimInsurance(Array.from(item.requestCommitment), false)
+        .accounts({
+          config: configPDA,
+          claim: item.claim,
+          providerBond: providerBondPDA,
+          vault: vaultPDA,
+          client: clientKeypair.publicKey,
+          clientTokenAccount,
+          platformTreasuryTokenAccount: platformTokenAccount,
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([clientKeypair])
+        .rpc();
+      paidOut += claims[i].refundAmount.toNumber() + claims[i].penaltyAmount.toNumber();
     }
-    console.log("");
-  });
 
-  it("2️⃣ Test liquidation conditions", async () => {
-    console.log("📊 Testing liquidation condition check...\n");
+    const bond = await program.account.providerBond.fetch(providerBondPDA);
+    assert.equal(bond.totalBond.toNumber(), BOND_AMOUNT.toNumber() - paidOut);
+    assert.equal(bond.lockedBond.toNumber(), 0);
+    assert.equal(bond.activeClaims, 0);
+    assert.isBelow(bond.totalBond.toNumber(), MIN_BOND.toNumber());
+    assert.isAbove(bond.undercollateralizedSince.toNumber(), 0);
+    assert.isFalse(bond.isLiquidated);
+  });
 
-    console.log("Conditions required for liquidation:");
-    console.log("  1. Provider bond below minimum");
-    console.log("  2. 24-hour grace period has passed");
-    console.log("  3. Provider has not replenished bond during grace period");
-    console.log("");
+  it("2️⃣ Liquidation waits for the grace period, then seizes the bond", async () => {
+    const config = await program.account.insuranceConfig.fetch(configPDA);
+    const setLiquidationParams = (params: {
+      liquidationGracePeriod: anchor.BN;
+      liquidationBonusBps: number;
+      maxLiquidationBonusBps: number;
+      liquidationBonusRamp: anchor.BN;
+      liquidationPenaltyBps: number;
+    }) =>
+      program.methods
+        .updateConfig({ ...noConfigChanges, ...params })
+        .accounts({ config: configPDA, authority: provider.wallet.publicKey })
+        .rpc();
 
     try {
-      const bond = await program.account.providerBond.fetch(providerBondPDA);
+      // A long grace period protects the Provider
+      await setLiquidationParams({
+        liquidationGracePeriod: new anchor.BN(86400),
+        liquidationBonusBps: 1000,
+        maxLiquidationBonusBps: 1000,
+        liquidationBonusRamp: new anchor.BN(0),
+        liquidationPenaltyBps: 0,
+      });
+      try {
+        await liquidate();
+        assert.fail("Liquidation inside the grace period should be rejected");
+      } catch (err) {
+        assert.include(err.toString(), "GracePeriodNotExpired");
+      }
 
-      if (bond.isLiquidated) {
-        console.log("  ⚠️  Provider already liquidated");
-        return;
+      // Once the (shortened) grace period has passed anyone may liquidate
+      await setLiquidationParams({
+        liquidationGracePeriod: new anchor.BN(GRACE_PERIOD),
+        liquidationBonusBps: 1000,
+        maxLiquidationBonusBps: 1000,
+        liquidationBonusRamp: new anchor.BN(0),
+        liquidationPenaltyBps: 0,
+      });
+      const bondBefore = await program.account.providerBond.fetch(providerBondPDA);
+      await waitForChainTime(
+        provider.connection,
+        bondBefore.undercollateralizedSince.toNumber() + GRACE_PERIOD
+      );
+
+      // A pause (when the Provider could not top up) restarts the grace period
+      const setPaused = (paused: boolean) =>
+        program.methods
+          .setPaused(paused)
+          .accounts({ config: configPDA, authority: provider.wallet.publicKey })
+          .rpc();
+      await setPaused(true);
+      await setPaused(false);
+      const unpausedAt = (
+        await program.account.insuranceConfig.fetch(configPDA)
+      ).unpausedAt.toNumber();
+      assert.isAtLeast(unpausedAt, bondBefore.undercollateralizedSince.toNumber());
+      try {
+        await liquidate();
+        assert.fail("Liquidation right after an unpause should be rejected");
+      } catch (err) {
+        assert.include(err.toString(), "GracePeriodNotExpired");
       }
+      await waitForChainTime(provider.connection, unpausedAt + GRACE_PERIOD);
 
-      const available = bond.totalBond.toNumber() - bond.lockedBond.toNumber();
-
-      if (available < bond.minBond.toNumber()) {
-        console.log("  ✅ Condition 1 met: Insufficient bond");
-
-        if (bond.undercollateralizedSince.toNumber() === 0) {
-          console.log("  ❌ Condition 2 not met: Undercollateralized timestamp not set");
-          console.log("     (This should be set automatically when bond first becomes insufficient)");
-        } else {
-          const gracePeriod = 86400; // 24 hours
-          const now = Math.floor(Date.now() / 1000);
-          const undercollateralizedTime = bond.undercollateralizedSince.toNumber();
-          const timePassed = now - undercollateralizedTime;
-
-          console.log("  Undercollateralized since:", new Date(undercollateralizedTime * 1000).toISOString());
-          console.log("  Current time:", new Date(now * 1000).toISOString());
-          console.log("  Time elapsed:", timePassed, "seconds (", Math.floor(timePassed / 3600), "hours)");
-          console.log("  Grace period:", gracePeriod, "seconds (24 hours)");
-
-          if (timePassed >= gracePeriod) {
-            console.log("  ✅ Condition 2 met: Grace period has passed");
-            console.log("");
-            console.log("  ✅ Can execute liquidation!");
-          } else {
-            console.log("  ❌ Condition 2 not met: Grace period not passed");
-            console.log("     Need to wait", (gracePeriod - timePassed), "more seconds");
-          }
-        }
-      } else {
-        console.log("  ❌ Condition 1 not met: Sufficient bond");
-        console.log("     Available bond:", available / 1_000_000, "tokens");
-        console.log("     Minimum bond:", bond.minBond.toNumber() / 1_000_000, "tokens");
+      const platformBefore = await balance(platformTokenAccount);
+      const vaultBefore = await balance(vaultPDA);
+      await liquidate();
+
+      const bond = await program.account.providerBond.fetch(providerBondPDA);
+      const seized = bondBefore.totalBond.toNumber();
+      const bonus = Math.floor((seized * 1000) / 10_000);
+      assert.isTrue(bond.isLiquidated);
+      assert.isAbove(bond.liquidatedAt.toNumber(), 0);
+      assert.equal(bond.totalBond.toNumber(), 0);
+      assert.equal(await balance(liquidatorTokenAccount), bonus);
+      assert.equal((await balance(platformTokenAccount)) - platformBefore, seized - bonus);
+      assert.equal(vaultBefore - (await balance(vaultPDA)), seized);
+
+      // A liquidated Provider cannot be liquidated again
+      try {
+        await liquidate();
+        assert.fail("Second liquidation should be rejected");
+      } catch (err) {
+        assert.include(err.toString(), "ProviderLiquidated");
       }
 
-    } catch (error: any) {
-      console.log("  ❌ Cannot check Provider status:", error.message);
+      console.log("✅ Liquidated", seized / 1_000_000, "tokens;", bonus / 1_000_000, "to the liquidator");
+    } finally {
+      await setLiquidationParams({
+        liquidationGracePeriod: config.liquidationGracePeriod,
+        liquidationBonusBps: config.liquidationBonusBps,
+        maxLiquidationBonusBps: config.maxLiquidationBonusBps,
+        liquidationBonusRamp: config.liquidationBonusRamp,
+        liquidationPenaltyBps: config.liquidationPenaltyBps,
+      });
     }
-    console.log("");
-  });
-
-  it("3️⃣ Test summary", () => {
-    console.log("");
-    console.log("═══════════════════════════════════════════════════════════");
-    console.log("📊 Provider Liquidation Test Summary");
-    console.log("═══════════════════════════════════════════════════════════");
-    console.log("");
-    console.log("ℹ️  Liquidation Test Notes:");
-    console.log("");
-    console.log("Since liquidation requires 24-hour grace period, full testing on devnet requires:");
-    console.log("  1. Reduce Provider bond below minimum through multiple timeout claims");
-    console.log("  2. Wait for 24-hour grace period");
-    console.log("  3. Execute liquidate_provider instruction");
-    console.log("");
-    console.log("In production environment, liquidation process serves to:");
-    console.log("  - Protect the system's overall solvency");
-    console.log("  - Give Provider time to replenish bond");
-    console.log("  - Prevent systemic risk");
-    console.log("");
-    console.log("💡 Recommendations:");
-    console.log("  - On localnet, can modify grace period to shorter time for testing");
-    console.log("  - On devnet, can check current Provider liquidation conditions");
-    console.log("  - Liquidation mechanism smart contract code is implemented and deployed");
-    console.log("");
-    console.log("═══════════════════════════════════════════════════════════");
   });
 });
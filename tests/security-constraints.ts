@@ -0,0 +1,368 @@
+/**
+ * Token Account Constraint Regression Test
+ *
+ * Each scenario substitutes an attacker-controlled token account where the
+ * program expects one owned by a specific wallet, and checks it is rejected:
+ * 1. Client pays a "provider" token account it controls
+ * 2. Client routes the platform penalty to itself on claim
+ * 3. Liquidator routes the seized bond to itself
+ * 4. Token account of a different mint is substituted
+ * 5. Attacker front-runs a client's request commitment
+ */
+
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { X402Insurance } from "../target/types/x402_insurance";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  createAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { requestCommitmentFor } from "./helpers";
+
+describe("Token account constraints", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.X402Insurance as Program<X402Insurance>;
+
+  let mint: PublicKey;
+  let otherMint: PublicKey;
+  let bondProvider: Keypair;
+  let client: Keypair;
+  let configPDA: PublicKey;
+  let vaultPDA: PublicKey;
+  let providerBondPDA: PublicKey;
+
+  let providerTokenAccount: PublicKey;
+  let clientTokenAccount: PublicKey;
+  let clientOwnedAccount: PublicKey;
+  let clientOtherMintAccount: PublicKey;
+  let treasuryTokenAccount: PublicKey;
+
+  const challenge = Buffer.alloc(32, 0x5a);
+  let requestCommitment: Buffer;
+  let claimPDA: PublicKey;
+
+  async function expectError(promise: Promise<unknown>, code: string) {
+    try {
+      await promise;
+      assert.fail(`Expected ${code}`);
+    } catch (err) {
+      assert.include(err.toString(), code);
+    }
+  }
+
+  before(async () => {
+    bondProvider = Keypair.generate();
+    client = Keypair.generate();
+
+    for (const kp of [bondProvider, client]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(
+          kp.publicKey,
+          2 * anchor.web3.LAMPORTS_PER_SOL
+        )
+      );
+    }
+
+    mint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      provider.wallet.publicKey,
+      null,
+      6
+    );
+    otherMint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      provider.wallet.publicKey,
+      null,
+      6
+    );
+
+    [configPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    [vaultPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), mint.toBuffer()],
+      program.programId
+    );
+    [providerBondPDA] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("provider_bond"),
+        bondProvider.publicKey.toBuffer(),
+        mint.toBuffer(),
+      ],
+      program.programId
+    );
+    requestCommitment = requestCommitmentFor(
+      bondProvider.publicKey,
+      client.publicKey,
+      challenge
+    );
+    [claimPDA] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("claim"),
+        client.publicKey.toBuffer(),
+        bondProvider.publicKey.toBuffer(),
+        requestCommitment,
+      ],
+      program.programId
+    );
+
+    // Initialize the protocol if this suite runs first
+    let config = await program.account.insuranceConfig.fetchNullable(configPDA);
+    if (!config) {
+      await program.methods
+        .initialize(200, new anchor.BN(300), new anchor.BN(86400), new anchor.BN(0))
+        .accounts({
+          config: configPDA,
+          platformTreasury: provider.wallet.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      config = await program.account.insuranceConfig.fetch(configPDA);
+    }
+
+    providerTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        bondProvider,
+        mint,
+        bondProvider.publicKey
+      )
+    ).address;
+    clientTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        client,
+        mint,
+        client.publicKey
+      )
+    ).address;
+    // A second account of the right mint, owned by the client
+    clientOwnedAccount = await createAccount(
+      provider.connection,
+      client,
+      mint,
+      client.publicKey,
+      Keypair.generate()
+    );
+    clientOtherMintAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        client,
+        otherMint,
+        client.publicKey
+      )
+    ).address;
+    treasuryTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        config.platformTreasury,
+        true
+      )
+    ).address;
+
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      providerTokenAccount,
+      provider.wallet.publicKey,
+      5_000_000
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      clientTokenAccount,
+      provider.wallet.publicKey,
+      5_000_000
+    );
+
+    await program.methods
+      .depositBond(new anchor.BN(5_000_000))
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        provider: bondProvider.publicKey,
+        providerTokenAccount,
+        mint,
+        vault: vaultPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([bondProvider])
+      .rpc();
+  });
+
+  function purchase(providerAccount: PublicKey, clientAccount: PublicKey) {
+    return program.methods
+      .purchaseInsurance(
+        Array.from(requestCommitment),
+        Array.from(challenge),
+        new anchor.BN(1_000_000),
+        new anchor.BN(5),
+        null
+      )
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        claim: claimPDA,
+        client: client.publicKey,
+        clientTokenAccount: clientAccount,
+        provider: bondProvider.publicKey,
+        providerTokenAccount: providerAccount,
+        mint,
+        vault: vaultPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([client])
+      .rpc();
+  }
+
+  it("Rejects payment to a provider token account the client controls", async () => {
+    await expectError(
+      purchase(clientOwnedAccount, clientTokenAccount),
+      "InvalidTokenAccountOwner"
+    );
+  });
+
+  it("Rejects a commitment squatted by another wallet", async () => {
+    const attacker = Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        attacker.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      )
+    );
+    const attackerTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        attacker,
+        mint,
+        attacker.publicKey
+      )
+    ).address;
+
+    // The attacker's claim PDA is scoped to its own wallet, so the client's is
+    // untouched, and the copied commitment does not match the attacker's challenge
+    const [attackerClaimPDA] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("claim"),
+        attacker.publicKey.toBuffer(),
+        bondProvider.publicKey.toBuffer(),
+        requestCommitment,
+      ],
+      program.programId
+    );
+    assert.isFalse(attackerClaimPDA.equals(claimPDA));
+
+    await expectError(
+      program.methods
+        .purchaseInsurance(
+          Array.from(requestCommitment),
+          Array.from(challenge),
+          new anchor.BN(1_000_000),
+          new anchor.BN(5),
+          null
+        )
+        .accounts({
+          config: configPDA,
+          providerBond: providerBondPDA,
+          claim: attackerClaimPDA,
+          client: attacker.publicKey,
+          clientTokenAccount: attackerTokenAccount,
+          provider: bondProvider.publicKey,
+          providerTokenAccount,
+          mint,
+          vault: vaultPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([attacker])
+        .rpc(),
+      "InvalidRequestCommitment"
+    );
+  });
+
+  it("Rejects token accounts of a different mint", async () => {
+    await expectError(
+      purchase(providerTokenAccount, clientOtherMintAccount),
+      "MintMismatch"
+    );
+  });
+
+  it("Rejects routing the claim penalty away from the treasury", async () => {
+    await purchase(providerTokenAccount, clientTokenAccount);
+
+    await expectError(
+      program.methods
+        .claimInsurance(Array.from(requestCommitment), false)
+        .accounts({
+          config: configPDA,
+          claim: claimPDA,
+          providerBond: providerBondPDA,
+          vault: vaultPDA,
+          client: client.publicKey,
+          clientTokenAccount,
+          platformTreasuryTokenAccount: clientOwnedAccount,
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([client])
+        .rpc(),
+      "InvalidTreasuryAccount"
+    );
+  });
+
+  it("Rejects routing a liquidation away from the treasury", async () => {
+    await expectError(
+      program.methods
+        .liquidateProvider()
+        .accounts({
+          config: configPDA,
+          providerBond: providerBondPDA,
+          vault: vaultPDA,
+          platformTreasuryTokenAccount: clientOwnedAccount,
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc(),
+      "InvalidTreasuryAccount"
+    );
+
+    // The legitimate treasury account passes the constraint and fails on health instead
+    await expectError(
+      program.methods
+        .liquidateProvider()
+        .accounts({
+          config: configPDA,
+          providerBond: providerBondPDA,
+          vault: vaultPDA,
+          platformTreasuryTokenAccount: treasuryTokenAccount,
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc(),
+      "ProviderNotUndercollateralized"
+    );
+  });
+});
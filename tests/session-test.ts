@@ -0,0 +1,332 @@
+/**
+ * Prepaid Session Test
+ *
+ * Test Scenario:
+ * 1. Client opens a session prepaying 10 requests; bond is locked once
+ * 2. Client records 2 requests, provider confirms 3 as served with the
+ *    client's signed receipt, which raises the request count to 3 (a receipt
+ *    the provider signed itself is rejected); the client records a 4th
+ * 3. Client cannot close before expiry, nor can a stranger; provider closes
+ *    and the 7 unserved requests are refunded (the 1 failed request also pays
+ *    the penalty)
+ */
+
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { X402Insurance } from "../target/types/x402_insurance";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { ed25519Instruction, sessionReceiptMessage } from "./helpers";
+
+describe("Prepaid sessions", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.X402Insurance as Program<X402Insurance>;
+
+  const PRICE = 10_000; // 0.01 USDC per request
+  const MAX_REQUESTS = 10;
+
+  let mint: PublicKey;
+  let bondProvider: Keypair;
+  let client: Keypair;
+  let configPDA: PublicKey;
+  let vaultPDA: PublicKey;
+  let providerBondPDA: PublicKey;
+  let policyPDA: PublicKey;
+  let providerTokenAccount: PublicKey;
+  let clientTokenAccount: PublicKey;
+  let treasuryTokenAccount: PublicKey;
+
+  before(async () => {
+    bondProvider = Keypair.generate();
+    client = Keypair.generate();
+    for (const wallet of [bondProvider, client]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(
+          wallet.publicKey,
+          10 * anchor.web3.LAMPORTS_PER_SOL
+        )
+      );
+    }
+
+    mint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      provider.wallet.publicKey,
+      null,
+      6
+    );
+
+    [configPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    [vaultPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), mint.toBuffer()],
+      program.programId
+    );
+    [providerBondPDA] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("provider_bond"),
+        bondProvider.publicKey.toBuffer(),
+        mint.toBuffer(),
+      ],
+      program.programId
+    );
+    [policyPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("provider_policy"), bondProvider.publicKey.toBuffer()],
+      program.programId
+    );
+
+    // Initialize the protocol if this suite runs first
+    let config = await program.account.insuranceConfig.fetchNullable(configPDA);
+    if (!config) {
+      await program.methods
+        .initialize(200, new anchor.BN(300), new anchor.BN(86400), new anchor.BN(0))
+        .accounts({
+          config: configPDA,
+          platformTreasury: provider.wallet.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      config = await program.account.insuranceConfig.fetch(configPDA);
+    }
+
+    providerTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        bondProvider,
+        mint,
+        bondProvider.publicKey
+      )
+    ).address;
+    clientTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        client,
+        mint,
+        client.publicKey
+      )
+    ).address;
+    treasuryTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        config.platformTreasury,
+        true
+      )
+    ).address;
+
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      providerTokenAccount,
+      provider.wallet.publicKey,
+      100_000_000
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      clientTokenAccount,
+      provider.wallet.publicKey,
+      100_000_000
+    );
+
+    await program.methods
+      .depositBond(new anchor.BN(50_000_000))
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        provider: bondProvider.publicKey,
+        providerTokenAccount,
+        mint,
+        vault: vaultPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([bondProvider])
+      .rpc();
+  });
+
+  it("Refunds unserved requests from the locked bond on close", async () => {
+    const nonce = new anchor.BN(1);
+    const [sessionPDA] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("session"),
+        client.publicKey.toBuffer(),
+        bondProvider.publicKey.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const bondBefore = await program.account.providerBond.fetch(providerBondPDA);
+
+    await program.methods
+      .openSession(
+        nonce,
+        new anchor.BN(PRICE),
+        new anchor.BN(MAX_REQUESTS),
+        new anchor.BN(60)
+      )
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        providerPolicy: policyPDA,
+        session: sessionPDA,
+        client: client.publicKey,
+        clientTokenAccount,
+        provider: bondProvider.publicKey,
+        providerTokenAccount,
+        mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([client])
+      .rpc();
+
+    let session = await program.account.session.fetch(sessionPDA);
+    let bond = await program.account.providerBond.fetch(providerBondPDA);
+    const lockedAmount = session.lockedAmount.toNumber();
+    assert.isAtLeast(lockedAmount, PRICE * MAX_REQUESTS);
+    assert.equal(
+      bond.lockedBond.toNumber(),
+      bondBefore.lockedBond.toNumber() + lockedAmount
+    );
+
+    const recordRequests = (ids: number[]) =>
+      program.methods
+        .recordSessionRequests(ids.map((i) => Array.from(Buffer.alloc(32, i))))
+        .accounts({ session: sessionPDA, client: client.publicKey })
+        .signers([client])
+        .rpc();
+    await recordRequests([1, 2]);
+
+    const confirmService = (servedCount: number, signer: Keypair) =>
+      program.methods
+        .confirmSessionService(new anchor.BN(servedCount))
+        .accounts({
+          session: sessionPDA,
+          provider: bondProvider.publicKey,
+          instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .preInstructions([
+          ed25519Instruction(signer, [
+            sessionReceiptMessage(
+              program.programId,
+              sessionPDA,
+              new anchor.BN(servedCount)
+            ),
+          ]),
+        ])
+        .signers([bondProvider])
+        .rpc();
+
+    // The provider cannot claim more than the budget covers
+    try {
+      await confirmService(MAX_REQUESTS + 1, client);
+      assert.fail("Serving beyond the budget should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidServedCount");
+    }
+
+    // Nor vouch for its own service
+    try {
+      await confirmService(3, bondProvider);
+      assert.fail("A provider-signed receipt should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidSignature");
+    }
+
+    // The client's receipt proves requests it never recorded
+    await confirmService(3, client);
+    session = await program.account.session.fetch(sessionPDA);
+    assert.equal(session.requestCount.toNumber(), 3);
+    assert.equal(session.servedCount.toNumber(), 3);
+
+    await recordRequests([4]);
+    session = await program.account.session.fetch(sessionPDA);
+    assert.equal(session.requestCount.toNumber(), 4);
+
+    const closeAccounts = (closer: PublicKey) => ({
+      config: configPDA,
+      session: sessionPDA,
+      providerBond: providerBondPDA,
+      vault: vaultPDA,
+      closer,
+      client: client.publicKey,
+      clientTokenAccount,
+      platformTreasuryTokenAccount: treasuryTokenAccount,
+      mint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    });
+
+    // Nobody outside the session may close it
+    const stranger = Keypair.generate();
+    try {
+      await program.methods
+        .closeSession()
+        .accounts(closeAccounts(stranger.publicKey))
+        .signers([stranger])
+        .rpc();
+      assert.fail("A stranger should not close the session");
+    } catch (err) {
+      assert.include(err.toString(), "NotSessionParty");
+    }
+
+    // Only the provider may close before the session expires
+    try {
+      await program.methods
+        .closeSession()
+        .accounts(closeAccounts(client.publicKey))
+        .signers([client])
+        .rpc();
+      assert.fail("Client should not close an unexpired session");
+    } catch (err) {
+      assert.include(err.toString(), "SessionNotExpired");
+    }
+
+    const clientBalanceBefore = (
+      await provider.connection.getTokenAccountBalance(clientTokenAccount)
+    ).value.amount;
+    await program.methods
+      .closeSession()
+      .accounts(closeAccounts(bondProvider.publicKey))
+      .signers([bondProvider])
+      .rpc();
+
+    const clientBalanceAfter = (
+      await provider.connection.getTokenAccountBalance(clientTokenAccount)
+    ).value.amount;
+    const refund = (MAX_REQUESTS - 3) * PRICE;
+    const penalty = Math.floor((PRICE * session.penaltyRateBps) / 10_000);
+    bond = await program.account.providerBond.fetch(providerBondPDA);
+
+    assert.equal(Number(clientBalanceAfter) - Number(clientBalanceBefore), refund);
+    assert.equal(
+      bond.totalBond.toNumber(),
+      bondBefore.totalBond.toNumber() - refund - penalty
+    );
+    assert.equal(bond.lockedBond.toNumber(), bondBefore.lockedBond.toNumber());
+    assert.isNull(await program.account.session.fetchNullable(sessionPDA));
+
+    console.log("✅ Session refunded", refund / 1_000_000, "USDC for unserved requests");
+  });
+});
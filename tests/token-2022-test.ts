@@ -0,0 +1,636 @@
+/**
+ * Token-2022 Bond Test
+ *
+ * Test Scenario:
+ * 1. Create a Token-2022 mint with a 1% transfer fee
+ * 2. Provider deposits bond; only the amount received is credited
+ * 3. Provider withdraws; bond is debited by the amount sent
+ * 4. A claim that times out reports the refund net of the fee, matching what
+ *    the client actually received
+ * 5. An interest-bearing mint insures and confirms like a plain mint
+ * 6. A mint with a permanent delegate is rejected
+ */
+
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { X402Insurance } from "../target/types/x402_insurance";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Ed25519Program,
+  Transaction,
+  sendAndConfirmTransaction,
+} from "@solana/web3.js";
+import {
+  TOKEN_2022_PROGRAM_ID,
+  ExtensionType,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializePermanentDelegateInstruction,
+  createInitializeInterestBearingMintInstruction,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  getAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { deliveryProofMessage, requestCommitmentFor, waitForChainTime } from "./helpers";
+
+describe("Token-2022 bonds", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.X402Insurance as Program<X402Insurance>;
+
+  const FEE_BASIS_POINTS = 100; // 1%
+  const MAX_FEE = BigInt(1_000_000_000);
+
+  let mint: PublicKey;
+  let bondProvider: Keypair;
+  let client: Keypair;
+  let providerTokenAccount: any;
+  let clientTokenAccount: any;
+  let treasuryTokenAccount: any;
+  let configPDA: PublicKey;
+  let vaultPDA: PublicKey;
+  let providerBondPDA: PublicKey;
+
+  const claimFor = (challenge: Buffer) => {
+    const requestCommitment = requestCommitmentFor(
+      bondProvider.publicKey,
+      client.publicKey,
+      challenge
+    );
+    const [claim] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("claim"),
+        client.publicKey.toBuffer(),
+        bondProvider.publicKey.toBuffer(),
+        requestCommitment,
+      ],
+      program.programId
+    );
+    return { challenge, requestCommitment, claim };
+  };
+
+  const balance = async (account: PublicKey) =>
+    Number(
+      (await getAccount(provider.connection, account, undefined, TOKEN_2022_PROGRAM_ID))
+        .amount
+    );
+
+  before(async () => {
+    bondProvider = Keypair.generate();
+    client = Keypair.generate();
+    for (const wallet of [bondProvider, client]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(
+          wallet.publicKey,
+          2 * anchor.web3.LAMPORTS_PER_SOL
+        )
+      );
+    }
+
+    // Create a Token-2022 mint with a transfer fee
+    const mintKeypair = Keypair.generate();
+    mint = mintKeypair.publicKey;
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const lamports =
+      await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+
+    const tx = new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: provider.wallet.publicKey,
+        newAccountPubkey: mint,
+        space: mintLen,
+        lamports,
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializeTransferFeeConfigInstruction(
+        mint,
+        provider.wallet.publicKey,
+        provider.wallet.publicKey,
+        FEE_BASIS_POINTS,
+        MAX_FEE,
+        TOKEN_2022_PROGRAM_ID
+      ),
+      createInitializeMintInstruction(
+        mint,
+        6,
+        provider.wallet.publicKey,
+        null,
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+    await sendAndConfirmTransaction(provider.connection, tx, [
+      provider.wallet.payer,
+      mintKeypair,
+    ]);
+
+    providerTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      bondProvider,
+      mint,
+      bondProvider.publicKey,
+      false,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      providerTokenAccount.address,
+      provider.wallet.publicKey,
+      10_000_000,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    [configPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    [vaultPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), mint.toBuffer()],
+      program.programId
+    );
+    [providerBondPDA] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("provider_bond"),
+        bondProvider.publicKey.toBuffer(),
+        mint.toBuffer(),
+      ],
+      program.programId
+    );
+
+    // Initialize the protocol if this suite runs first
+    let config = await program.account.insuranceConfig.fetchNullable(configPDA);
+    if (!config) {
+      await program.methods
+        .initialize(200, new anchor.BN(300), new anchor.BN(86400), new anchor.BN(0))
+        .accounts({
+          config: configPDA,
+          platformTreasury: provider.wallet.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      config = await program.account.insuranceConfig.fetch(configPDA);
+    }
+
+    clientTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      client,
+      mint,
+      client.publicKey,
+      false,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    treasuryTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      config.platformTreasury,
+      true,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      clientTokenAccount.address,
+      provider.wallet.publicKey,
+      10_000_000,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+  });
+
+  it("Credits the bond with the amount received net of transfer fees", async () => {
+    const depositAmount = 5_000_000;
+
+    await program.methods
+      .depositBond(new anchor.BN(depositAmount))
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        provider: bondProvider.publicKey,
+        providerTokenAccount: providerTokenAccount.address,
+        mint,
+        vault: vaultPDA,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([bondProvider])
+      .rpc();
+
+    const bond = await program.account.providerBond.fetch(providerBondPDA);
+    const vault = await getAccount(
+      provider.connection,
+      vaultPDA,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    const fee = (depositAmount * FEE_BASIS_POINTS) / 10_000;
+    assert.equal(bond.totalBond.toNumber(), depositAmount - fee);
+    assert.equal(Number(vault.amount), bond.totalBond.toNumber());
+
+    console.log("✅ Bond credited", bond.totalBond.toNumber(), "after", fee, "fee");
+  });
+
+  it("Debits the bond by the amount sent on withdrawal", async () => {
+    const bondBefore = await program.account.providerBond.fetch(providerBondPDA);
+    const withdrawAmount = 1_000_000;
+
+    await program.methods
+      .withdrawBond(new anchor.BN(withdrawAmount))
+      .accounts({
+        providerBond: providerBondPDA,
+        vault: vaultPDA,
+        provider: bondProvider.publicKey,
+        providerTokenAccount: providerTokenAccount.address,
+        mint,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([bondProvider])
+      .rpc();
+
+    const bondAfter = await program.account.providerBond.fetch(providerBondPDA);
+    const vault = await getAccount(
+      provider.connection,
+      vaultPDA,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    assert.equal(
+      bondBefore.totalBond.sub(bondAfter.totalBond).toNumber(),
+      withdrawAmount
+    );
+    assert.equal(Number(vault.amount), bondAfter.totalBond.toNumber());
+
+    console.log("✅ Vault balance tracks total bond");
+  });
+
+  it("Reports the refund the client received net of the transfer fee", async () => {
+    const paymentAmount = 1_000_000;
+    const item = claimFor(Buffer.alloc(32, 0x27));
+
+    await program.methods
+      .purchaseInsurance(
+        Array.from(item.requestCommitment),
+        Array.from(item.challenge),
+        new anchor.BN(paymentAmount),
+        new anchor.BN(1),
+        null
+      )
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        claim: item.claim,
+        client: client.publicKey,
+        clientTokenAccount: clientTokenAccount.address,
+        provider: bondProvider.publicKey,
+        providerTokenAccount: providerTokenAccount.address,
+        mint,
+        vault: vaultPDA,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([client])
+      .rpc();
+
+    const claim = await program.account.insuranceClaim.fetch(item.claim);
+    await waitForChainTime(provider.connection, claim.deadline.toNumber());
+
+    const clientBefore = await balance(clientTokenAccount.address);
+    const txSignature = await program.methods
+      .claimInsurance(Array.from(item.requestCommitment), false)
+      .accounts({
+        config: configPDA,
+        claim: item.claim,
+        providerBond: providerBondPDA,
+        vault: vaultPDA,
+        client: client.publicKey,
+        clientTokenAccount: clientTokenAccount.address,
+        platformTreasuryTokenAccount: treasuryTokenAccount.address,
+        mint,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([client])
+      .rpc({ commitment: "confirmed" });
+    const received = (await balance(clientTokenAccount.address)) - clientBefore;
+
+    const tx = await provider.connection.getTransaction(txSignature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const eventParser = new anchor.EventParser(
+      program.programId,
+      new anchor.BorshCoder(program.idl)
+    );
+    const claimed = [...eventParser.parseLogs(tx.meta.logMessages)].find(
+      (e) => e.name === "insuranceClaimed"
+    );
+    assert.ok(claimed, "InsuranceClaimed event not emitted");
+
+    // The event carries the gross refund and what actually arrived
+    const fee = (claim.refundAmount.toNumber() * FEE_BASIS_POINTS) / 10_000;
+    assert.equal(claimed.data.refundAmount.toNumber(), claim.refundAmount.toNumber());
+    assert.equal(claimed.data.clientReceived.toNumber(), received);
+    assert.equal(received, claim.refundAmount.toNumber() - fee);
+
+    console.log("✅ Client received", received, "of a", claim.refundAmount.toNumber(), "refund");
+  });
+
+  it("Insures and confirms on an interest-bearing mint", async () => {
+    // Interest only changes the UI amount, so raw balances still add up
+    const mintKeypair = Keypair.generate();
+    const interestMint = mintKeypair.publicKey;
+    const mintLen = getMintLen([ExtensionType.InterestBearingConfig]);
+    const lamports =
+      await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+
+    const tx = new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: provider.wallet.publicKey,
+        newAccountPubkey: interestMint,
+        space: mintLen,
+        lamports,
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializeInterestBearingMintInstruction(
+        interestMint,
+        provider.wallet.publicKey,
+        500, // 5% a year
+        TOKEN_2022_PROGRAM_ID
+      ),
+      createInitializeMintInstruction(
+        interestMint,
+        6,
+        provider.wallet.publicKey,
+        null,
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+    await sendAndConfirmTransaction(provider.connection, tx, [
+      provider.wallet.payer,
+      mintKeypair,
+    ]);
+
+    const [interestProviderAccount, interestClientAccount] = await Promise.all(
+      [bondProvider, client].map((wallet) =>
+        getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          wallet,
+          interestMint,
+          wallet.publicKey,
+          false,
+          undefined,
+          undefined,
+          TOKEN_2022_PROGRAM_ID
+        )
+      )
+    );
+    const interestTreasuryAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      interestMint,
+      (await program.account.insuranceConfig.fetch(configPDA)).platformTreasury,
+      true,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    for (const account of [interestProviderAccount, interestClientAccount]) {
+      await mintTo(
+        provider.connection,
+        provider.wallet.payer,
+        interestMint,
+        account.address,
+        provider.wallet.publicKey,
+        10_000_000,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+    }
+
+    const [interestVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), interestMint.toBuffer()],
+      program.programId
+    );
+    const [interestBond] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("provider_bond"),
+        bondProvider.publicKey.toBuffer(),
+        interestMint.toBuffer(),
+      ],
+      program.programId
+    );
+
+    const bondAmount = 5_000_000;
+    await program.methods
+      .depositBond(new anchor.BN(bondAmount))
+      .accounts({
+        config: configPDA,
+        providerBond: interestBond,
+        provider: bondProvider.publicKey,
+        providerTokenAccount: interestProviderAccount.address,
+        mint: interestMint,
+        vault: interestVault,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([bondProvider])
+      .rpc();
+    let bond = await program.account.providerBond.fetch(interestBond);
+    assert.equal(bond.totalBond.toNumber(), bondAmount);
+
+    const paymentAmount = 1_000_000;
+    const item = claimFor(Buffer.alloc(32, 0x28));
+    const providerBefore = await balance(interestProviderAccount.address);
+    await program.methods
+      .purchaseInsurance(
+        Array.from(item.requestCommitment),
+        Array.from(item.challenge),
+        new anchor.BN(paymentAmount),
+        new anchor.BN(5),
+        null
+      )
+      .accounts({
+        config: configPDA,
+        providerBond: interestBond,
+        claim: item.claim,
+        client: client.publicKey,
+        clientTokenAccount: interestClientAccount.address,
+        provider: bondProvider.publicKey,
+        providerTokenAccount: interestProviderAccount.address,
+        mint: interestMint,
+        vault: interestVault,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([client])
+      .rpc();
+
+    const purchased = await program.account.insuranceClaim.fetch(item.claim);
+    bond = await program.account.providerBond.fetch(interestBond);
+    assert.equal(
+      (await balance(interestProviderAccount.address)) - providerBefore,
+      paymentAmount
+    );
+    assert.equal(bond.lockedBond.toNumber(), purchased.lockedAmount.toNumber());
+
+    const proofIx = Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: bondProvider.secretKey,
+      message: deliveryProofMessage(
+        program.programId,
+        item.claim,
+        item.requestCommitment,
+        purchased.paymentAmount,
+        purchased.deadline
+      ),
+    });
+    await program.methods
+      .confirmService(
+        Array.from(item.requestCommitment),
+        Array.from(proofIx.data.subarray(48, 112)),
+        10_000,
+        false
+      )
+      .accounts({
+        config: configPDA,
+        claim: item.claim,
+        providerBond: interestBond,
+        provider: bondProvider.publicKey,
+        providerTokenAccount: interestProviderAccount.address,
+        vault: interestVault,
+        clientTokenAccount: interestClientAccount.address,
+        platformTreasuryTokenAccount: interestTreasuryAccount.address,
+        mint: interestMint,
+        instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .preInstructions([proofIx])
+      .signers([bondProvider])
+      .rpc();
+
+    const confirmed = await program.account.insuranceClaim.fetch(item.claim);
+    bond = await program.account.providerBond.fetch(interestBond);
+    assert.deepEqual(confirmed.status, { confirmed: {} });
+    assert.equal(bond.lockedBond.toNumber(), 0);
+    assert.equal(bond.totalBond.toNumber(), bondAmount);
+    assert.equal(await balance(interestVault), bondAmount);
+
+    console.log("✅ Interest-bearing mint insured and confirmed");
+  });
+
+  it("Rejects a mint with a permanent delegate", async () => {
+    // The delegate could drain the shared vault, so no bond may use this mint
+    const mintKeypair = Keypair.generate();
+    const delegatedMint = mintKeypair.publicKey;
+    const mintLen = getMintLen([ExtensionType.PermanentDelegate]);
+    const lamports =
+      await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+
+    const tx = new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: provider.wallet.publicKey,
+        newAccountPubkey: delegatedMint,
+        space: mintLen,
+        lamports,
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializePermanentDelegateInstruction(
+        delegatedMint,
+        provider.wallet.publicKey,
+        TOKEN_2022_PROGRAM_ID
+      ),
+      createInitializeMintInstruction(
+        delegatedMint,
+        6,
+        provider.wallet.publicKey,
+        null,
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+    await sendAndConfirmTransaction(provider.connection, tx, [
+      provider.wallet.payer,
+      mintKeypair,
+    ]);
+
+    const delegatedTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      bondProvider,
+      delegatedMint,
+      bondProvider.publicKey,
+      false,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      delegatedMint,
+      delegatedTokenAccount.address,
+      provider.wallet.publicKey,
+      1_000_000,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    const [delegatedVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), delegatedMint.toBuffer()],
+      program.programId
+    );
+    const [delegatedBond] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("provider_bond"),
+        bondProvider.publicKey.toBuffer(),
+        delegatedMint.toBuffer(),
+      ],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .depositBond(new anchor.BN(1_000_000))
+        .accounts({
+          config: configPDA,
+          providerBond: delegatedBond,
+          provider: bondProvider.publicKey,
+          providerTokenAccount: delegatedTokenAccount.address,
+          mint: delegatedMint,
+          vault: delegatedVault,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([bondProvider])
+        .rpc();
+      assert.fail("Permanent delegate mint should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "UnsupportedMintExtension");
+    }
+
+    console.log("✅ Permanent delegate mint rejected");
+  });
+});
@@ -0,0 +1,531 @@
+/**
+ * Liquidation Wind-Down Test
+ *
+ * Test Scenario:
+ * 1. Provider with one pending claim is liquidated (grace period shortened to 0)
+ *    by a keeper who earns a bonus out of the seized bond
+ * 2. The pending claim still settles against the locked bond
+ * 3. Once nothing is locked, the residual bond is swept to the treasury
+ * 4. The provider is reinstated with a fresh bond and authority approval
+ * 5. A partial liquidation seizes only the configured penalty
+ */
+
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { X402Insurance } from "../target/types/x402_insurance";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { noConfigChanges, requestCommitmentFor } from "./helpers";
+
+describe("Liquidation wind-down", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.X402Insurance as Program<X402Insurance>;
+
+  let mint: PublicKey;
+  let bondProvider: Keypair;
+  let client: Keypair;
+  let configPDA: PublicKey;
+  let vaultPDA: PublicKey;
+  let providerBondPDA: PublicKey;
+  let providerTokenAccount: PublicKey;
+  let clientTokenAccount: PublicKey;
+  let treasuryTokenAccount: PublicKey;
+
+  const balance = async (account: PublicKey) =>
+    Number((await provider.connection.getTokenAccountBalance(account)).value.amount);
+
+  const newClaim = (seed: number) => {
+    const challenge = Buffer.alloc(32, seed);
+    const requestCommitment = requestCommitmentFor(
+      bondProvider.publicKey,
+      client.publicKey,
+      challenge
+    );
+    const [claim] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("claim"),
+        client.publicKey.toBuffer(),
+        bondProvider.publicKey.toBuffer(),
+        requestCommitment,
+      ],
+      program.programId
+    );
+    return { challenge, requestCommitment, claim };
+  };
+
+  const purchase = (item: ReturnType<typeof newClaim>) =>
+    program.methods
+      .purchaseInsurance(
+        Array.from(item.requestCommitment),
+        Array.from(item.challenge),
+        new anchor.BN(1_000_000),
+        new anchor.BN(5),
+        null
+      )
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        claim: item.claim,
+        client: client.publicKey,
+        clientTokenAccount,
+        provider: bondProvider.publicKey,
+        providerTokenAccount,
+        mint,
+        vault: vaultPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([client])
+      .rpc();
+
+  const liquidate = (liquidator: Keypair, liquidatorTokenAccount: PublicKey) =>
+    program.methods
+      .liquidateProvider()
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        vault: vaultPDA,
+        liquidator: liquidator.publicKey,
+        liquidatorTokenAccount,
+        platformTreasuryTokenAccount: treasuryTokenAccount,
+        mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([liquidator])
+      .rpc();
+
+  const sweep = () =>
+    program.methods
+      .sweepLiquidatedBond()
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        vault: vaultPDA,
+        platformTreasuryTokenAccount: treasuryTokenAccount,
+        mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+  before(async () => {
+    bondProvider = Keypair.generate();
+    client = Keypair.generate();
+    for (const wallet of [bondProvider, client]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(
+          wallet.publicKey,
+          10 * anchor.web3.LAMPORTS_PER_SOL
+        )
+      );
+    }
+
+    mint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      provider.wallet.publicKey,
+      null,
+      6
+    );
+
+    [configPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    [vaultPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), mint.toBuffer()],
+      program.programId
+    );
+    [providerBondPDA] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("provider_bond"),
+        bondProvider.publicKey.toBuffer(),
+        mint.toBuffer(),
+      ],
+      program.programId
+    );
+
+    // Initialize the protocol if this suite runs first
+    let config = await program.account.insuranceConfig.fetchNullable(configPDA);
+    if (!config) {
+      await program.methods
+        .initialize(200, new anchor.BN(300), new anchor.BN(86400), new anchor.BN(0))
+        .accounts({
+          config: configPDA,
+          platformTreasury: provider.wallet.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      config = await program.account.insuranceConfig.fetch(configPDA);
+    }
+
+    providerTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        bondProvider,
+        mint,
+        bondProvider.publicKey
+      )
+    ).address;
+    clientTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        client,
+        mint,
+        client.publicKey
+      )
+    ).address;
+    treasuryTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        config.platformTreasury,
+        true
+      )
+    ).address;
+
+    for (const account of [providerTokenAccount, clientTokenAccount]) {
+      await mintTo(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        account,
+        provider.wallet.publicKey,
+        100_000_000
+      );
+    }
+
+    await program.methods
+      .depositBond(new anchor.BN(10_000_000))
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        provider: bondProvider.publicKey,
+        providerTokenAccount,
+        mint,
+        vault: vaultPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([bondProvider])
+      .rpc();
+  });
+
+  it("Pending claims settle after liquidation and the residual is swept", async () => {
+    const item = newClaim(0x44);
+    await purchase(item);
+
+    const liquidator = Keypair.generate();
+    const liquidatorTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        liquidator.publicKey
+      )
+    ).address;
+
+    // The bonus cannot start above its maximum
+    try {
+      await program.methods
+        .updateConfig({ ...noConfigChanges, liquidationBonusBps: 600, maxLiquidationBonusBps: 500 })
+        .accounts({ config: configPDA, authority: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("Bonus above its maximum should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidLiquidationBonus");
+    }
+
+    // Raising min_bond above the bond starts the grace period; a zero grace
+    // period makes the provider liquidatable at once
+    const config = await program.account.insuranceConfig.fetch(configPDA);
+    await program.methods
+      .setMinBond(new anchor.BN(1_000_000_000))
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+    await program.methods
+      .updateConfig({
+        ...noConfigChanges,
+        liquidationGracePeriod: new anchor.BN(0),
+        liquidationBonusBps: 500,
+        maxLiquidationBonusBps: 500,
+        liquidationBonusRamp: new anchor.BN(0),
+      })
+      .accounts({ config: configPDA, authority: provider.wallet.publicKey })
+      .rpc();
+
+    const treasuryBefore = await balance(treasuryTokenAccount);
+    try {
+      await liquidate(liquidator, liquidatorTokenAccount);
+    } finally {
+      await program.methods
+        .updateConfig({
+          ...noConfigChanges,
+          liquidationGracePeriod: config.liquidationGracePeriod,
+          liquidationBonusBps: config.liquidationBonusBps,
+          maxLiquidationBonusBps: config.maxLiquidationBonusBps,
+          liquidationBonusRamp: config.liquidationBonusRamp,
+        })
+        .accounts({ config: configPDA, authority: provider.wallet.publicKey })
+        .rpc();
+    }
+
+    let bond = await program.account.providerBond.fetch(providerBondPDA);
+    const claim = await program.account.insuranceClaim.fetch(item.claim);
+    assert.isTrue(bond.isLiquidated);
+    assert.isAbove(bond.liquidatedAt.toNumber(), 0);
+    assert.equal(bond.totalBond.toNumber(), claim.lockedAmount.toNumber());
+    assert.equal(bond.lockedBond.toNumber(), claim.lockedAmount.toNumber());
+    const seized = 10_000_000 - claim.lockedAmount.toNumber();
+    const bonus = Math.floor((seized * 500) / 10_000);
+    assert.equal(await balance(liquidatorTokenAccount), bonus);
+    assert.equal((await balance(treasuryTokenAccount)) - treasuryBefore, seized - bonus);
+
+    // No new exposure, no withdrawals and no sweep while a claim is pending
+    try {
+      await purchase(newClaim(0x45));
+      assert.fail("Liquidated provider should not be insurable");
+    } catch (err) {
+      assert.include(err.toString(), "ProviderLiquidated");
+    }
+    try {
+      await program.methods
+        .withdrawBond(new anchor.BN(1))
+        .accounts({
+          providerBond: providerBondPDA,
+          provider: bondProvider.publicKey,
+          providerTokenAccount,
+          mint,
+          vault: vaultPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([bondProvider])
+        .rpc();
+      assert.fail("Liquidated provider should not withdraw");
+    } catch (err) {
+      assert.include(err.toString(), "ProviderLiquidated");
+    }
+    try {
+      await sweep();
+      assert.fail("Sweep should wait for pending claims");
+    } catch (err) {
+      assert.include(err.toString(), "WindDownIncomplete");
+    }
+
+    // The client can still settle the pending claim
+    await program.methods
+      .acknowledgeDelivery(Array.from(item.requestCommitment))
+      .accounts({
+        claim: item.claim,
+        providerBond: providerBondPDA,
+        submitter: client.publicKey,
+        instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .signers([client])
+      .rpc();
+
+    bond = await program.account.providerBond.fetch(providerBondPDA);
+    assert.equal(bond.lockedBond.toNumber(), 0);
+    assert.equal(bond.activeClaims, 0);
+    const residual = bond.totalBond.toNumber();
+
+    const treasuryBeforeSweep = await balance(treasuryTokenAccount);
+    await sweep();
+
+    bond = await program.account.providerBond.fetch(providerBondPDA);
+    assert.equal(bond.totalBond.toNumber(), 0);
+    assert.equal((await balance(treasuryTokenAccount)) - treasuryBeforeSweep, residual);
+
+    console.log("✅ Wind-down settled the pending claim and swept", residual / 1_000_000, "tokens");
+  });
+
+  it("Liquidated provider is reinstated with a fresh bond", async () => {
+    const config = await program.account.insuranceConfig.fetch(configPDA);
+    const reinstate = (amount: number, approve: boolean) =>
+      program.methods
+        .reinstateProvider(new anchor.BN(amount))
+        .accounts({
+          config: configPDA,
+          providerBond: providerBondPDA,
+          provider: bondProvider.publicKey,
+          providerTokenAccount,
+          vault: vaultPDA,
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          authority: approve ? provider.wallet.publicKey : null,
+        })
+        .signers([bondProvider])
+        .rpc();
+
+    // Plain deposits into a liquidated bond are refused
+    try {
+      await program.methods
+        .depositBond(new anchor.BN(1_000_000))
+        .accounts({
+          config: configPDA,
+          providerBond: providerBondPDA,
+          provider: bondProvider.publicKey,
+          providerTokenAccount,
+          mint,
+          vault: vaultPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([bondProvider])
+        .rpc();
+      assert.fail("Deposit into a liquidated bond should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "ProviderLiquidated");
+    }
+
+    await program.methods
+      .setMinBond(new anchor.BN(1_000_000))
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+    await program.methods
+      .updateConfig({
+        ...noConfigChanges,
+        reinstatementBondBps: 20_000,
+        reinstatementRequiresApproval: true,
+      })
+      .accounts({ config: configPDA, authority: provider.wallet.publicKey })
+      .rpc();
+
+    try {
+      try {
+        await reinstate(2_000_000, false);
+        assert.fail("Reinstatement should need approval");
+      } catch (err) {
+        assert.include(err.toString(), "ReinstatementNotApproved");
+      }
+      try {
+        await reinstate(1_000_000, true);
+        assert.fail("Deposit below 2x min_bond should be rejected");
+      } catch (err) {
+        assert.include(err.toString(), "InsufficientReinstatementBond");
+      }
+
+      // Like any deposit, reinstatement waits out a protocol pause
+      await program.methods
+        .setPaused(true)
+        .accounts({ config: configPDA, authority: provider.wallet.publicKey })
+        .rpc();
+      try {
+        await reinstate(2_000_000, true);
+        assert.fail("Reinstatement while paused should be rejected");
+      } catch (err) {
+        assert.include(err.toString(), "ProtocolPaused");
+      } finally {
+        await program.methods
+          .setPaused(false)
+          .accounts({ config: configPDA, authority: provider.wallet.publicKey })
+          .rpc();
+      }
+
+      await reinstate(2_000_000, true);
+    } finally {
+      await program.methods
+        .updateConfig({
+          ...noConfigChanges,
+          reinstatementBondBps: config.reinstatementBondBps,
+          reinstatementRequiresApproval: config.reinstatementRequiresApproval,
+        })
+        .accounts({ config: configPDA, authority: provider.wallet.publicKey })
+        .rpc();
+    }
+
+    const bond = await program.account.providerBond.fetch(providerBondPDA);
+    assert.isFalse(bond.isLiquidated);
+    assert.equal(bond.liquidatedAt.toNumber(), 0);
+    assert.equal(bond.undercollateralizedSince.toNumber(), 0);
+    assert.equal(bond.totalBond.toNumber(), 2_000_000);
+
+    // And can be insured again
+    await purchase(newClaim(0x46));
+
+    console.log("✅ Provider reinstated");
+  });
+
+  it("Partial liquidation seizes only the penalty and keeps the provider operating", async () => {
+    const config = await program.account.insuranceConfig.fetch(configPDA);
+    const liquidator = Keypair.generate();
+    const liquidatorTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        mint,
+        liquidator.publicKey
+      )
+    ).address;
+
+    await program.methods
+      .setMinBond(new anchor.BN(5_000_000))
+      .accounts({
+        config: configPDA,
+        providerBond: providerBondPDA,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+    const bondBefore = await program.account.providerBond.fetch(providerBondPDA);
+
+    // 10% of the 5 token min_bond is seized per liquidation
+    await program.methods
+      .updateConfig({
+        ...noConfigChanges,
+        liquidationGracePeriod: new anchor.BN(0),
+        liquidationPenaltyBps: 1000,
+      })
+      .accounts({ config: configPDA, authority: provider.wallet.publicKey })
+      .rpc();
+    try {
+      await liquidate(liquidator, liquidatorTokenAccount);
+    } finally {
+      await program.methods
+        .updateConfig({
+          ...noConfigChanges,
+          liquidationGracePeriod: config.liquidationGracePeriod,
+          liquidationPenaltyBps: config.liquidationPenaltyBps,
+        })
+        .accounts({ config: configPDA, authority: provider.wallet.publicKey })
+        .rpc();
+    }
+
+    const bond = await program.account.providerBond.fetch(providerBondPDA);
+    assert.isFalse(bond.isLiquidated);
+    assert.equal(bond.totalBond.toNumber(), bondBefore.totalBond.toNumber() - 500_000);
+    assert.equal(bond.lockedBond.toNumber(), bondBefore.lockedBond.toNumber());
+    assert.isAtLeast(
+      bond.undercollateralizedSince.toNumber(),
+      bondBefore.undercollateralizedSince.toNumber()
+    );
+    assert.equal(bond.slashCounts[3], bondBefore.slashCounts[3] + 1);
+    assert.equal(bond.totalSlashed.toNumber(), bondBefore.totalSlashed.toNumber() + 500_000);
+
+    console.log("✅ Partial liquidation seized 0.5 tokens, provider still active");
+  });
+});